@@ -1,16 +1,110 @@
-use crate::services::youtube_service::{handle_youtube_scraper, Video};
+use std::sync::Arc;
+use crate::services::youtube_service::{
+    handle_youtube_scraper, SearchType, Video, VideoFilters, VideoResults, VideoSorting,
+};
 use crate::services::job_service::{handle_job_scraper, Job};
-use crate::services::cache::{clear_cache, remove_cache};
-use actix_web::{HttpResponse, Responder, get, web};
-use serde::Deserialize;
+use crate::services::video_detail_service::{handle_video_details, Chapter, VideoDetails};
+use crate::services::channel_service::handle_channel_videos;
+use crate::services::playlist_service::{handle_playlist, Playlist};
+use crate::services::transcript_service::{handle_transcript, CaptionTrack, Transcript, TranscriptSegment};
+use crate::services::cache::{self, clear_cache, CacheKeyInfo, CacheStats, CacheStatus, Cached, KeyFilter};
+use crate::services::refresh_service::{refresh_cache_key, RefreshResult};
+use crate::handlers::auth::{AdminSecurity, AdminToken};
+use crate::handlers::error::{error_response, ErrorBody};
+use crate::services::error::ServiceError;
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpResponse, HttpResponseBuilder, Responder, ResponseError, delete, get, post, web};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
 use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
+/// v2 response body: the items plus where they came from and how old they are.
+#[derive(Serialize, ToSchema)]
+struct Envelope<T> {
+    #[schema(value_type = Vec<T>)]
+    items: Arc<Vec<T>>,
+    total: usize,
+    /// Upstream the items come from: `youtube` or `remoteok`.
+    source: &'static str,
+    from_cache: bool,
+    cache_status: CacheStatus,
+    /// Upstream failed and `items` is a built-in fallback list.
+    is_fallback: bool,
+    /// When the items were fetched from upstream (RFC 3339).
+    fetched_at: String,
+    /// Seconds since the items were fetched from upstream.
+    cache_age_secs: u64,
+    /// Pass as `page_token` to get the next page; absent on the last page and
+    /// for endpoints without paging.
+    next_page_token: Option<String>,
+}
+
+impl<T> Envelope<T> {
+    fn new<U>(items: Arc<Vec<T>>, cached: &Cached<U>, source: &'static str, is_fallback: bool) -> Self {
+        Envelope {
+            total: items.len(),
+            items,
+            source,
+            from_cache: cached.status != CacheStatus::Miss,
+            cache_status: cached.status,
+            is_fallback,
+            fetched_at: DateTime::from_timestamp(cached.fetched_at as i64, 0)
+                .unwrap_or_default()
+                .to_rfc3339(),
+            cache_age_secs: cached.age(),
+            next_page_token: None,
+        }
+    }
+
+    fn with_next_page_token(mut self, token: Option<String>) -> Self {
+        self.next_page_token = token;
+        self
+    }
+}
+
 #[derive(Deserialize, ToSchema)]
 struct VideoStruct {
     query: String,
     limit: Option<u32>,
-    sorting: Option<String>,
+    sorting: Option<VideoSorting>,
+    /// `playlist` to search for playlists instead of videos.
+    r#type: Option<SearchType>,
+    min_duration_secs: Option<u64>,
+    max_duration_secs: Option<u64>,
+    exclude_shorts: Option<bool>,
+    exclude_live: Option<bool>,
+    /// `next_page_token` from a previous response.
+    page_token: Option<String>,
+}
+
+/// How a transcript is returned.
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum TranscriptFormat {
+    /// JSON with timed segments and the available tracks.
+    #[default]
+    Segments,
+    /// Plain text, one caption per line.
+    Text,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct TranscriptStruct {
+    /// Language code of the captions, e.g. `en`; defaults to the first
+    /// uploaded track.
+    lang: Option<String>,
+    /// Prefer YouTube's auto-generated captions.
+    auto_generated: Option<bool>,
+    format: Option<TranscriptFormat>,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ChannelVideosStruct {
+    /// Comma-separated channel IDs; defaults to the curated channels.
+    channel_ids: Option<String>,
+    query: Option<String>,
+    limit: Option<u32>,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -27,6 +121,12 @@ struct CacheRefreshStruct {
     cache_key: String,
 }
 
+#[derive(Deserialize, ToSchema)]
+struct CacheKeyFilterStruct {
+    prefix: Option<String>,
+    pattern: Option<String>,
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/echo",
@@ -57,28 +157,252 @@ async fn health_check() -> impl Responder {
     params(
         ("query" = String, Query, description = "Search query for YouTube videos"),
         ("limit" = Option<u32>, Query, description = "Maximum number of videos to return (default: 5)"),
-        ("sorting" = Option<String>, Query, description = "Sorting method (default: relevance)")
+        ("sorting" = Option<VideoSorting>, Query, description = "Result order: relevance (default), upload_date, view_count or rating"),
+        ("type" = Option<SearchType>, Query, description = "video (default), or playlist to return playlists as entries with type \"playlist\""),
+        ("min_duration_secs" = Option<u64>, Query, description = "Only videos at least this long"),
+        ("max_duration_secs" = Option<u64>, Query, description = "Only videos at most this long"),
+        ("exclude_shorts" = Option<bool>, Query, description = "Leave out Shorts (default: false)"),
+        ("exclude_live" = Option<bool>, Query, description = "Leave out live streams and upcoming premieres (default: false)"),
+        ("page_token" = Option<String>, Query, description = "Next page token from a previous response")
     ),
     responses(
-        (status = 200, description = "List of YouTube videos", body = [Video]),
-        (status = 500, description = "Internal server error", body = String)
+        (status = 200, description = "List of YouTube videos", body = [Video], headers(
+            ("X-Cache" = String, description = "HIT, STALE or MISS"),
+            ("Age" = u64, description = "Seconds since the videos were fetched from YouTube"),
+            ("X-Next-Page-Token" = String, description = "Pass as page_token to get the next page; absent on the last page")
+        )),
+        (status = 400, description = "Invalid query parameters or page token", body = ErrorBody),
+        (status = 502, description = "YouTube failed while fetching a playlist search or a page after the first; the first page of a video search falls back to built-in videos instead", body = ErrorBody)
     )
 )]
 #[get("/api/v1/resources/video")]
 async fn get_video(vquery: web::Query<VideoStruct>) -> impl Responder {
+    match fetch_videos(&vquery).await {
+        Ok(results) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &results.page);
+            if let Some(token) = &results.page.value.next_page_token {
+                response.insert_header(("X-Next-Page-Token", token.as_str()));
+            }
+            response.json(results.page.value.videos.as_ref())
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/resources/video",
+    params(
+        ("query" = String, Query, description = "Search query for YouTube videos"),
+        ("limit" = Option<u32>, Query, description = "Maximum number of videos to return (default: 5)"),
+        ("sorting" = Option<VideoSorting>, Query, description = "Result order: relevance (default), upload_date, view_count or rating"),
+        ("type" = Option<SearchType>, Query, description = "video (default), or playlist to return playlists as entries with type \"playlist\""),
+        ("min_duration_secs" = Option<u64>, Query, description = "Only videos at least this long"),
+        ("max_duration_secs" = Option<u64>, Query, description = "Only videos at most this long"),
+        ("exclude_shorts" = Option<bool>, Query, description = "Leave out Shorts (default: false)"),
+        ("exclude_live" = Option<bool>, Query, description = "Leave out live streams and upcoming premieres (default: false)"),
+        ("page_token" = Option<String>, Query, description = "Next page token from a previous response")
+    ),
+    responses(
+        (status = 200, description = "YouTube videos with cache and fallback metadata", body = Envelope<Video>),
+        (status = 400, description = "Invalid query parameters or page token", body = ErrorBody),
+        (status = 502, description = "YouTube failed while fetching a playlist search or a page after the first; the first page of a video search falls back to built-in videos instead", body = ErrorBody)
+    )
+)]
+#[get("/api/v2/resources/video")]
+async fn get_video_v2(vquery: web::Query<VideoStruct>) -> impl Responder {
+    match fetch_videos(&vquery).await {
+        Ok(results) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &results.page);
+            let page = &results.page.value;
+            response.json(
+                Envelope::new(page.videos.clone(), &results.page, "youtube", results.is_fallback)
+                    .with_next_page_token(page.next_page_token.clone()),
+            )
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+async fn fetch_videos(vquery: &VideoStruct) -> Result<VideoResults, ServiceError> {
     let query = &vquery.query;
     let limit = vquery.limit.unwrap_or(5);
-    let sorting = vquery.sorting.as_deref().unwrap_or("relevance");
-    log::info!("Fetching YouTube videos for query: {}, limit: {}, sorting: {}", query, limit, sorting);
+    let sorting = vquery.sorting.unwrap_or_default();
+    let search_type = vquery.r#type.unwrap_or_default();
+    log::info!(
+        "Fetching YouTube {}s for query: {}, limit: {}, sorting: {}",
+        search_type.as_str(),
+        query,
+        limit,
+        sorting.as_str()
+    );
 
-    match handle_youtube_scraper(query, limit).await {
+    let filters = VideoFilters {
+        min_duration_secs: vquery.min_duration_secs,
+        max_duration_secs: vquery.max_duration_secs,
+        exclude_shorts: vquery.exclude_shorts.unwrap_or(false),
+        exclude_live: vquery.exclude_live.unwrap_or(false),
+    };
+    let results = handle_youtube_scraper(query, limit, sorting, search_type, filters, vquery.page_token.as_deref()).await;
+    match &results {
+        Ok(results) => log::info!("Returning {} YouTube videos", results.page.value.videos.len()),
+        Err(e) => log::error!("YouTube scraper error: {}", e),
+    }
+    results
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/resources/video/{id}",
+    params(
+        ("id" = String, Path, description = "YouTube video ID")
+    ),
+    responses(
+        (status = 200, description = "Details of a single video", body = VideoDetails, headers(
+            ("X-Cache" = String, description = "HIT, STALE or MISS"),
+            ("Age" = u64, description = "Seconds since the details were fetched from YouTube")
+        )),
+        (status = 400, description = "Malformed video ID", body = ErrorBody),
+        (status = 404, description = "Video does not exist or is unavailable", body = ErrorBody),
+        (status = 429, description = "Rate limited by YouTube", body = ErrorBody),
+        (status = 502, description = "YouTube failed or returned an unexpected page", body = ErrorBody),
+        (status = 504, description = "YouTube timed out", body = ErrorBody)
+    )
+)]
+#[get("/api/v1/resources/video/{id}")]
+async fn get_video_details(id: web::Path<String>) -> impl Responder {
+    log::info!("Fetching YouTube video details for: {}", id);
+    match handle_video_details(&id).await {
+        Ok(details) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &details);
+            response.json(details.value.as_ref())
+        }
+        Err(e) => {
+            log::error!("Video details error for {}: {}", id, e);
+            e.error_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/resources/video/{id}/transcript",
+    params(
+        ("id" = String, Path, description = "YouTube video ID"),
+        ("lang" = Option<String>, Query, description = "Language code of the captions, e.g. en (default: the first uploaded track)"),
+        ("auto_generated" = Option<bool>, Query, description = "Prefer auto-generated captions over uploaded ones (default: false)"),
+        ("format" = Option<TranscriptFormat>, Query, description = "segments (default) for timed JSON segments, text for plain text")
+    ),
+    responses(
+        (status = 200, description = "Captions of the video; plain text with format=text", body = Transcript, headers(
+            ("X-Cache" = String, description = "HIT, STALE or MISS"),
+            ("Age" = u64, description = "Seconds since the captions were fetched from YouTube")
+        )),
+        (status = 400, description = "Malformed video ID or language code", body = ErrorBody),
+        (status = 404, description = "Video unavailable, or no captions in the requested language", body = ErrorBody),
+        (status = 429, description = "Rate limited by YouTube", body = ErrorBody),
+        (status = 502, description = "YouTube failed or returned unexpected captions", body = ErrorBody),
+        (status = 504, description = "YouTube timed out", body = ErrorBody)
+    )
+)]
+#[get("/api/v1/resources/video/{id}/transcript")]
+async fn get_video_transcript(id: web::Path<String>, tquery: web::Query<TranscriptStruct>) -> impl Responder {
+    let lang = tquery.lang.as_deref();
+    log::info!("Fetching YouTube transcript for: {}, lang: {}", id, lang.unwrap_or("default"));
+    match handle_transcript(&id, lang, tquery.auto_generated.unwrap_or(false)).await {
+        Ok(transcript) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &transcript);
+            match tquery.format.unwrap_or_default() {
+                TranscriptFormat::Segments => response.json(transcript.value.as_ref()),
+                TranscriptFormat::Text => response
+                    .content_type("text/plain; charset=utf-8")
+                    .body(transcript.value.plain_text()),
+            }
+        }
+        Err(e) => {
+            log::error!("Transcript error for {}: {}", id, e);
+            e.error_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/resources/playlist/{id}",
+    params(
+        ("id" = String, Path, description = "YouTube playlist ID")
+    ),
+    responses(
+        (status = 200, description = "The playlist's videos in order, with their total duration", body = Playlist, headers(
+            ("X-Cache" = String, description = "HIT, STALE or MISS"),
+            ("Age" = u64, description = "Seconds since the playlist was fetched from YouTube")
+        )),
+        (status = 400, description = "Malformed playlist ID", body = ErrorBody),
+        (status = 404, description = "Playlist does not exist or is private", body = ErrorBody),
+        (status = 429, description = "Rate limited by YouTube", body = ErrorBody),
+        (status = 502, description = "YouTube failed or returned an unexpected page", body = ErrorBody),
+        (status = 504, description = "YouTube timed out", body = ErrorBody)
+    )
+)]
+#[get("/api/v1/resources/playlist/{id}")]
+async fn get_playlist(id: web::Path<String>) -> impl Responder {
+    log::info!("Fetching YouTube playlist: {}", id);
+    match handle_playlist(&id).await {
+        Ok(playlist) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &playlist);
+            response.json(playlist.value.as_ref())
+        }
+        Err(e) => {
+            log::error!("Playlist error for {}: {}", id, e);
+            e.error_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/resources/channels/videos",
+    params(
+        ("channel_ids" = Option<String>, Query, description = "Comma-separated channel IDs (UC...); defaults to the configured curated channels"),
+        ("query" = Option<String>, Query, description = "Only videos whose title or description contains every word"),
+        ("limit" = Option<u32>, Query, description = "Maximum number of videos to return (default: 10)")
+    ),
+    responses(
+        (status = 200, description = "Latest videos of the channels, newest first", body = [Video], headers(
+            ("X-Cache" = String, description = "HIT, STALE or MISS, for the oldest channel feed"),
+            ("Age" = u64, description = "Seconds since the oldest channel feed was fetched from YouTube")
+        )),
+        (status = 400, description = "Malformed channel ID, too many channels, or no channels given or configured", body = ErrorBody),
+        (status = 404, description = "The only channel asked for does not exist", body = ErrorBody),
+        (status = 502, description = "No channel feed could be read", body = ErrorBody)
+    )
+)]
+#[get("/api/v1/resources/channels/videos")]
+async fn get_channel_videos(cquery: web::Query<ChannelVideosStruct>) -> impl Responder {
+    let channel_ids: Vec<String> = cquery
+        .channel_ids
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    log::info!("Fetching YouTube channel videos for {} channels", channel_ids.len());
+    match handle_channel_videos(&channel_ids, cquery.query.as_deref(), cquery.limit.unwrap_or(10)).await {
         Ok(videos) => {
-            log::info!("Returning {} YouTube videos", videos.len());
-            HttpResponse::Ok().json(videos)
+            log::info!("Returning {} channel videos", videos.value.len());
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &videos);
+            response.json(videos.value.as_ref())
         }
         Err(e) => {
-            log::error!("YouTube scraper error: {}", e);
-            HttpResponse::InternalServerError().body(format!("Failed to fetch videos: {}", e))
+            log::error!("Channel videos error: {}", e);
+            e.error_response()
         }
     }
 }
@@ -94,12 +418,59 @@ async fn get_video(vquery: web::Query<VideoStruct>) -> impl Responder {
         ("job_type" = Option<String>, Query, description = "Filter for job type (e.g., Full-time, Contract)")
     ),
     responses(
-        (status = 200, description = "List of jobs", body = [Job]),
-        (status = 500, description = "Internal server error", body = String)
+        (status = 200, description = "List of jobs", body = [Job], headers(
+            ("X-Cache" = String, description = "HIT, STALE or MISS"),
+            ("Age" = u64, description = "Seconds since the jobs were fetched")
+        )),
+        (status = 400, description = "Invalid query parameters", body = ErrorBody),
+        (status = 429, description = "Rate limited by RemoteOK", body = ErrorBody),
+        (status = 502, description = "RemoteOK failed or returned an unexpected response", body = ErrorBody),
+        (status = 504, description = "RemoteOK timed out", body = ErrorBody)
     )
 )]
 #[get("/api/v1/jobs")]
 async fn get_jobs(jquery: web::Query<JobStruct>) -> impl Responder {
+    match fetch_jobs(&jquery).await {
+        Ok(jobs) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &jobs);
+            response.json(jobs.value.as_ref())
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/jobs",
+    params(
+        ("query" = String, Query, description = "Search query for jobs"),
+        ("limit" = Option<u32>, Query, description = "Maximum number of jobs to return (default: 10)"),
+        ("location" = Option<String>, Query, description = "Location filter for jobs"),
+        ("remote_only" = Option<bool>, Query, description = "Filter for remote-only jobs"),
+        ("job_type" = Option<String>, Query, description = "Filter for job type (e.g., Full-time, Contract)")
+    ),
+    responses(
+        (status = 200, description = "Jobs with cache metadata", body = Envelope<Job>),
+        (status = 400, description = "Invalid query parameters", body = ErrorBody),
+        (status = 429, description = "Rate limited by RemoteOK", body = ErrorBody),
+        (status = 502, description = "RemoteOK failed or returned an unexpected response", body = ErrorBody),
+        (status = 504, description = "RemoteOK timed out", body = ErrorBody)
+    )
+)]
+#[get("/api/v2/jobs")]
+async fn get_jobs_v2(jquery: web::Query<JobStruct>) -> impl Responder {
+    match fetch_jobs(&jquery).await {
+        Ok(jobs) => {
+            let mut response = HttpResponse::Ok();
+            cache_headers(&mut response, &jobs);
+            response.json(Envelope::new(jobs.value.clone(), &jobs, "remoteok", false))
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+async fn fetch_jobs(jquery: &JobStruct) -> Result<Cached<Vec<Job>>, ServiceError> {
     let query = &jquery.query;
     let limit = jquery.limit.unwrap_or(10);
     let location = jquery.location.as_deref().unwrap_or("");
@@ -109,54 +480,193 @@ async fn get_jobs(jquery: web::Query<JobStruct>) -> impl Responder {
     log::info!("Fetching jobs for query: {}, limit: {}, location: {}, remote_only: {:?}, job_type: {:?}", 
               query, limit, location, remote_only, job_type);
 
-    match handle_job_scraper(query, limit, location, remote_only, job_type).await {
-        Ok(jobs) => {
-            log::info!("Returning {} jobs", jobs.len());
-            HttpResponse::Ok().json(jobs)
-        }
-        Err(e) => {
-            log::error!("Job scraper error: {}", e);
-            HttpResponse::InternalServerError().body(format!("Failed to fetch jobs: {}", e))
-        }
+    let jobs = handle_job_scraper(query, limit, location, remote_only, job_type).await;
+    match &jobs {
+        Ok(jobs) => log::info!("Returning {} jobs", jobs.value.len()),
+        Err(e) => log::error!("Job scraper error: {}", e),
     }
+    jobs
+}
+
+/// Sets `X-Cache` and `Age` so v1 clients can tell cached data from live data.
+fn cache_headers<T>(response: &mut HttpResponseBuilder, cached: &Cached<T>) {
+    let status = match cached.status {
+        CacheStatus::Hit => "HIT",
+        CacheStatus::Stale => "STALE",
+        CacheStatus::Miss => "MISS",
+    };
+    response
+        .insert_header(("X-Cache", status))
+        .insert_header((header::AGE, cached.age()));
 }
 
 #[utoipa::path(
-    get,
-    path = "/api/v1/cache/clear",
+    delete,
+    path = "/api/v1/cache",
     responses(
-        (status = 200, description = "Clear all cache entries", body = String)
-    )
+        (status = 200, description = "Clear all cache entries", body = String),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 403, description = "Admin endpoints are disabled", body = ErrorBody)
+    ),
+    security(("admin_bearer" = []), ("admin_header" = []))
 )]
-#[get("/api/v1/cache/clear")]
-async fn clear_all_cache() -> impl Responder {
+#[delete("/api/v1/cache")]
+async fn clear_all_cache(_admin: AdminToken) -> impl Responder {
     log::info!("Clearing all cache");
-    clear_cache();
+    clear_cache().await;
     HttpResponse::Ok().body("Cache cleared")
 }
 
 #[utoipa::path(
-    get,
+    post,
     path = "/api/v1/cache/refresh",
     params(
         ("cache_key" = String, Query, description = "Cache key to refresh")
     ),
     responses(
-        (status = 200, description = "Refresh specific cache entry", body = String)
-    )
+        (status = 200, description = "Entry re-fetched from upstream", body = RefreshResult),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 403, description = "Admin endpoints are disabled", body = ErrorBody),
+        (status = 404, description = "Key is not cached or cannot be refreshed", body = ErrorBody),
+        (status = 429, description = "Rate limited by upstream; the cached entry is left as it was", body = ErrorBody),
+        (status = 502, description = "Upstream fetch failed; the cached entry is left as it was", body = ErrorBody),
+        (status = 504, description = "Upstream timed out; the cached entry is left as it was", body = ErrorBody)
+    ),
+    security(("admin_bearer" = []), ("admin_header" = []))
 )]
-#[get("/api/v1/cache/refresh")]
-async fn refresh_cache(query: web::Query<CacheRefreshStruct>) -> impl Responder {
+#[post("/api/v1/cache/refresh")]
+async fn refresh_cache(_admin: AdminToken, query: web::Query<CacheRefreshStruct>) -> impl Responder {
     let cache_key = &query.cache_key;
     log::info!("Refreshing cache for key: {}", cache_key);
-    remove_cache(cache_key);
-    HttpResponse::Ok().body(format!("Cache refreshed for key: {}", cache_key))
+    match refresh_cache_key(cache_key).await {
+        Ok(Some(result)) => {
+            log::info!("Refreshed {} with {} items in {} ms", cache_key, result.items, result.duration_ms);
+            HttpResponse::Ok().json(result)
+        }
+        Ok(None) => error_response(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            format!("Cache key not found or cannot be refreshed: {}", cache_key),
+        ),
+        Err(e) => {
+            log::error!("Cache refresh error for {}: {}", cache_key, e);
+            e.error_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cache/keys",
+    params(
+        ("prefix" = Option<String>, Query, description = "Only list keys starting with this prefix"),
+        ("pattern" = Option<String>, Query, description = "Only list keys matching this glob (e.g. videos_rust_*)")
+    ),
+    responses(
+        (status = 200, description = "Live cache entries with age, TTL and size", body = [CacheKeyInfo]),
+        (status = 400, description = "Invalid glob pattern", body = ErrorBody),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 403, description = "Admin endpoints are disabled", body = ErrorBody)
+    ),
+    security(("admin_bearer" = []), ("admin_header" = []))
+)]
+#[get("/api/v1/cache/keys")]
+async fn list_cache_keys(_admin: AdminToken, query: web::Query<CacheKeyFilterStruct>) -> impl Responder {
+    let filter = match KeyFilter::new(query.prefix.as_deref(), query.pattern.as_deref()) {
+        Ok(filter) => filter,
+        Err(e) => return ServiceError::InvalidParams(format!("invalid pattern: {}", e)).error_response(),
+    };
+    HttpResponse::Ok().json(cache::list_keys(&filter).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cache/stats",
+    responses(
+        (status = 200, description = "Cache size and hit/miss/eviction counters", body = CacheStats),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 403, description = "Admin endpoints are disabled", body = ErrorBody)
+    ),
+    security(("admin_bearer" = []), ("admin_header" = []))
+)]
+#[get("/api/v1/cache/stats")]
+async fn cache_stats(_admin: AdminToken) -> impl Responder {
+    HttpResponse::Ok().json(cache::stats().await)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cache/keys",
+    params(
+        ("prefix" = Option<String>, Query, description = "Invalidate keys starting with this prefix"),
+        ("pattern" = Option<String>, Query, description = "Invalidate keys matching this glob (e.g. videos_rust_*)")
+    ),
+    responses(
+        (status = 200, description = "Keys that were invalidated", body = [String]),
+        (status = 400, description = "Missing or invalid filter", body = ErrorBody),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 403, description = "Admin endpoints are disabled", body = ErrorBody)
+    ),
+    security(("admin_bearer" = []), ("admin_header" = []))
+)]
+#[delete("/api/v1/cache/keys")]
+async fn invalidate_cache_keys(_admin: AdminToken, query: web::Query<CacheKeyFilterStruct>) -> impl Responder {
+    let filter = match KeyFilter::new(query.prefix.as_deref(), query.pattern.as_deref()) {
+        Ok(filter) => filter,
+        Err(e) => return ServiceError::InvalidParams(format!("invalid pattern: {}", e)).error_response(),
+    };
+    // Clearing everything has its own endpoint; don't do it by accident here.
+    if filter.is_empty() {
+        return ServiceError::InvalidParams("either prefix or pattern is required".to_string()).error_response();
+    }
+    let removed = cache::invalidate(&filter).await;
+    log::info!("Invalidated {} cache keys", removed.len());
+    HttpResponse::Ok().json(removed)
 }
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(echo, health_check, get_video, get_jobs, clear_all_cache, refresh_cache),
-    components(schemas(Video, Job, VideoStruct, JobStruct, CacheRefreshStruct))
+    paths(
+        echo,
+        health_check,
+        get_video,
+        get_video_v2,
+        get_video_details,
+        get_video_transcript,
+        get_playlist,
+        get_channel_videos,
+        get_jobs,
+        get_jobs_v2,
+        clear_all_cache,
+        refresh_cache,
+        list_cache_keys,
+        cache_stats,
+        invalidate_cache_keys
+    ),
+    components(schemas(
+        Video,
+        VideoSorting,
+        SearchType,
+        VideoDetails,
+        Chapter,
+        Transcript,
+        TranscriptSegment,
+        CaptionTrack,
+        TranscriptFormat,
+        Playlist,
+        Job,
+        VideoStruct,
+        JobStruct,
+        ChannelVideosStruct,
+        CacheRefreshStruct,
+        CacheKeyFilterStruct,
+        CacheKeyInfo,
+        CacheStats,
+        RefreshResult,
+        ErrorBody,
+        CacheStatus
+    )),
+    modifiers(&AdminSecurity)
 )]
 struct ApiDoc;
 
@@ -0,0 +1,74 @@
+use std::future::{ready, Ready};
+use actix_web::dev::Payload;
+use actix_web::error::InternalError;
+use actix_web::http::StatusCode;
+use actix_web::{Error, FromRequest, HttpRequest};
+use utoipa::Modify;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use crate::handlers::error::error_response;
+use crate::settings;
+
+pub const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// Extractor guarding admin endpoints. Requests must carry the configured
+/// `admin.token` as a bearer token or in the `X-Admin-Token` header.
+pub struct AdminToken;
+
+impl FromRequest for AdminToken {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authorize(req))
+    }
+}
+
+fn authorize(req: &HttpRequest) -> Result<AdminToken, Error> {
+    let Some(expected) = settings::get().admin.token.as_deref().filter(|t| !t.is_empty()) else {
+        return Err(reject(StatusCode::FORBIDDEN, "forbidden", "Admin endpoints are disabled: no admin token configured"));
+    };
+
+    let headers = req.headers();
+    let provided = headers
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get(ADMIN_TOKEN_HEADER).and_then(|v| v.to_str().ok()));
+
+    match provided {
+        Some(token) if constant_time_eq(token.trim().as_bytes(), expected.as_bytes()) => Ok(AdminToken),
+        _ => {
+            log::warn!("Rejected unauthorized admin request to {}", req.path());
+            Err(reject(StatusCode::UNAUTHORIZED, "unauthorized", "Missing or invalid admin token"))
+        }
+    }
+}
+
+fn reject(status: StatusCode, error: &str, message: &'static str) -> Error {
+    InternalError::from_response(message, error_response(status, error, message)).into()
+}
+
+// Avoids leaking how much of the token matched through response timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Registers the admin security schemes in the OpenAPI document.
+pub struct AdminSecurity;
+
+impl Modify for AdminSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "admin_header",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(ADMIN_TOKEN_HEADER))),
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use actix_web::error::QueryPayloadError;
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+use utoipa::ToSchema;
+use crate::services::error::ServiceError;
+
+/// Body of every error response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Machine-readable error code, e.g. `upstream_timeout` or `invalid_params`.
+    pub error: String,
+    /// Human-readable description of what went wrong.
+    pub message: String,
+}
+
+pub fn error_response(status: StatusCode, error: &str, message: impl Into<String>) -> HttpResponse {
+    HttpResponse::build(status).json(ErrorBody {
+        error: error.to_string(),
+        message: message.into(),
+    })
+}
+
+impl ResponseError for ServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ServiceError::Upstream(_) | ServiceError::Parse(_) => StatusCode::BAD_GATEWAY,
+            ServiceError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ServiceError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ServiceError::InvalidParams(_) => StatusCode::BAD_REQUEST,
+            ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        error_response(self.status_code(), self.code(), self.to_string())
+    }
+}
+
+/// Reports malformed query strings (a missing `query`, a non-numeric `limit`,
+/// ...) with the same body as other invalid parameters.
+pub fn query_error(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    ServiceError::InvalidParams(err.to_string()).into()
+}
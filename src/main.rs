@@ -1,16 +1,55 @@
-use actix_web::{App, HttpServer};
+use actix_web::{web, App, HttpServer};
 mod handlers;
 mod services;
+mod settings;
 use actix_web::middleware::Logger;
 use actix_governor::Governor;
 use dotenv::dotenv;
-use env_logger;
-use handlers::api::{clear_all_cache, configure_swagger, echo, get_jobs, get_video, health_check, refresh_cache};
+use std::path::PathBuf;
+use std::time::Duration;
+use settings::CacheBackendKind;
+use handlers::api::{
+    cache_stats, clear_all_cache, configure_swagger, echo, get_channel_videos, get_jobs, get_jobs_v2, get_playlist,
+    get_video, get_video_details, get_video_transcript, get_video_v2, health_check, invalidate_cache_keys,
+    list_cache_keys, refresh_cache,
+};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let settings = settings::get();
+    services::cache::init(&settings.cache)
+        .await
+        .map_err(|e| std::io::Error::other(format!("Failed to initialize cache: {}", e)))?;
+    services::cache::spawn_sweeper(Duration::from_secs(settings.cache.sweep_interval_secs));
+
+    // Redis persists on its own; snapshots only make sense for the in-memory backend.
+    let snapshot_path = settings
+        .cache
+        .snapshot_path
+        .as_ref()
+        .filter(|_| settings.cache.backend == CacheBackendKind::Memory)
+        .map(PathBuf::from);
+    if let Some(path) = &snapshot_path {
+        match services::cache::load_snapshot(path).await {
+            Ok(count) => log::info!("Restored {} cache entries from {:?}", count, path),
+            Err(e) => log::warn!("Failed to load cache snapshot from {:?}: {}", path, e),
+        }
+        services::cache::spawn_snapshotter(
+            path.clone(),
+            Duration::from_secs(settings.cache.snapshot_interval_secs),
+        );
+    }
+
+    services::job_service::spawn_feed_refresher(Duration::from_secs(settings.jobs.feed_refresh_secs));
+    services::warmup_service::spawn_warmer(&settings.warmup);
+
+    if settings.admin.token.as_deref().is_none_or(str::is_empty) {
+        log::warn!("No admin token configured; cache admin endpoints are disabled");
+    }
+
     log::info!("Starting server on http://127.0.0.1:8081");
     
     HttpServer::new(|| {
@@ -23,15 +62,34 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .wrap(governor_conf)
             .wrap(Logger::default())
+            .app_data(web::QueryConfig::default().error_handler(handlers::error::query_error))
             .configure(configure_swagger)
             .service(get_video)
+            .service(get_video_v2)
+            .service(get_video_details)
+            .service(get_video_transcript)
+            .service(get_playlist)
+            .service(get_channel_videos)
             .service(get_jobs)
+            .service(get_jobs_v2)
             .service(clear_all_cache)
             .service(refresh_cache)
+            .service(list_cache_keys)
+            .service(cache_stats)
+            .service(invalidate_cache_keys)
             .service(echo)
             .service(health_check)
     })
     .bind(("127.0.0.1", 8081))?
     .run()
-    .await
+    .await?;
+
+    if let Some(path) = &snapshot_path {
+        match services::cache::save_snapshot(path).await {
+            Ok(count) => log::info!("Saved {} cache entries to {:?}", count, path),
+            Err(e) => log::error!("Failed to save cache snapshot to {:?}: {}", path, e),
+        }
+    }
+
+    Ok(())
 }
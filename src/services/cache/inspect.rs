@@ -0,0 +1,209 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use regex::Regex;
+use serde::Serialize;
+use utoipa::ToSchema;
+use super::{backend, now, CacheEntry, EntryKind};
+
+static HITS: AtomicU64 = AtomicU64::new(0);
+static STALE_HITS: AtomicU64 = AtomicU64::new(0);
+static NEGATIVE_HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static EVICTIONS: AtomicU64 = AtomicU64::new(0);
+
+pub(super) fn record_hit(stale: bool) {
+    if stale {
+        STALE_HITS.fetch_add(1, Ordering::Relaxed);
+    } else {
+        HITS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(super) fn record_negative_hit() {
+    NEGATIVE_HITS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(super) fn record_miss() {
+    MISSES.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(super) fn record_eviction() {
+    EVICTIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheKeyInfo {
+    pub key: String,
+    pub kind: EntryKind,
+    pub age_secs: u64,
+    pub ttl_secs: u64,
+    /// Seconds until the entry goes stale; zero once it has.
+    pub fresh_for_secs: u64,
+    pub stale: bool,
+    /// Size of the key plus the serialized value.
+    pub size_bytes: usize,
+}
+
+/// Counters are per process and reset on restart; entry totals reflect the
+/// shared backend.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheStats {
+    pub entries: usize,
+    /// Entries recording an empty result or an upstream error.
+    pub negative_entries: usize,
+    pub size_bytes: usize,
+    pub hits: u64,
+    pub stale_hits: u64,
+    /// Requests answered from an empty or error entry.
+    pub negative_hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// Selects cache keys by prefix and/or glob pattern (`*` matches any run of
+/// characters, `?` a single one). An empty filter matches every key.
+#[derive(Debug, Default)]
+pub struct KeyFilter {
+    prefix: Option<String>,
+    pattern: Option<Regex>,
+}
+
+impl KeyFilter {
+    pub fn new(prefix: Option<&str>, glob: Option<&str>) -> Result<Self, regex::Error> {
+        let pattern = glob
+            .map(|glob| {
+                let escaped = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+                Regex::new(&format!("^{}$", escaped))
+            })
+            .transpose()?;
+        Ok(KeyFilter {
+            prefix: prefix.map(|p| p.to_string()),
+            pattern,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefix.is_none() && self.pattern.is_none()
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        self.prefix.as_ref().is_none_or(|prefix| key.starts_with(prefix.as_str()))
+            && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(key))
+    }
+}
+
+/// Lists live cache entries matching `filter`, oldest first.
+pub async fn list_keys(filter: &KeyFilter) -> Vec<CacheKeyInfo> {
+    let now = now();
+    let mut keys: Vec<CacheKeyInfo> = backend()
+        .entries()
+        .await
+        .into_iter()
+        .filter(|(key, entry)| !entry.is_expired(now) && filter.matches(key))
+        .map(|(key, entry)| {
+            let age_secs = now.saturating_sub(entry.timestamp);
+            CacheKeyInfo {
+                size_bytes: entry.size_bytes,
+                key,
+                kind: entry.kind,
+                age_secs,
+                ttl_secs: entry.ttl,
+                fresh_for_secs: entry.ttl.saturating_sub(age_secs),
+                stale: entry.is_stale(now),
+            }
+        })
+        .collect();
+    keys.sort_by(|a, b| b.age_secs.cmp(&a.age_secs).then_with(|| a.key.cmp(&b.key)));
+    keys
+}
+
+/// Removes every entry matching `filter` and returns the removed keys.
+pub async fn invalidate(filter: &KeyFilter) -> Vec<String> {
+    let backend = backend();
+    let keys: Vec<String> = backend
+        .keys()
+        .await
+        .into_iter()
+        .filter(|key| filter.matches(key))
+        .collect();
+    for key in &keys {
+        backend.remove(key).await;
+    }
+    keys
+}
+
+pub async fn stats() -> CacheStats {
+    let now = now();
+    let live: Vec<(String, CacheEntry)> = backend()
+        .entries()
+        .await
+        .into_iter()
+        .filter(|(_, entry)| !entry.is_expired(now))
+        .collect();
+    CacheStats {
+        entries: live.len(),
+        negative_entries: live.iter().filter(|(_, entry)| entry.kind != EntryKind::Value).count(),
+        size_bytes: live.iter().map(|(_, entry)| entry.size_bytes).sum(),
+        hits: HITS.load(Ordering::Relaxed),
+        stale_hits: STALE_HITS.load(Ordering::Relaxed),
+        negative_hits: NEGATIVE_HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+        evictions: EVICTIONS.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matching<'a>(filter: &KeyFilter, keys: &[&'a str]) -> Vec<&'a str> {
+        keys.iter().copied().filter(|key| filter.matches(key)).collect()
+    }
+
+    const KEYS: [&str; 5] = [
+        "jobs_rust_10__false_",
+        "jobs_rusty_5__true_",
+        "jobs_python_10__false_",
+        "videos_rust_5_relevance",
+        "watch_abcdefghijk",
+    ];
+
+    #[test]
+    fn star_matches_any_run_of_characters() {
+        let filter = KeyFilter::new(None, Some("jobs_rust_*")).unwrap();
+        assert_eq!(matching(&filter, &KEYS), vec!["jobs_rust_10__false_"]);
+        let filter = KeyFilter::new(None, Some("*_rust_*")).unwrap();
+        assert_eq!(matching(&filter, &KEYS), vec!["jobs_rust_10__false_", "videos_rust_5_relevance"]);
+    }
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        let filter = KeyFilter::new(None, Some("jobs_rust?_5__true_")).unwrap();
+        assert_eq!(matching(&filter, &KEYS), vec!["jobs_rusty_5__true_"]);
+        let filter = KeyFilter::new(None, Some("jobs_rust?_10__false_")).unwrap();
+        assert!(matching(&filter, &KEYS).is_empty());
+    }
+
+    #[test]
+    fn patterns_match_the_whole_key_literally() {
+        // `.` and `_` are not wildcards, and there is no implicit `*` at the end.
+        let filter = KeyFilter::new(None, Some("watch.abcdefghijk")).unwrap();
+        assert!(matching(&filter, &KEYS).is_empty());
+        let filter = KeyFilter::new(None, Some("jobs_rust")).unwrap();
+        assert!(matching(&filter, &KEYS).is_empty());
+    }
+
+    #[test]
+    fn prefix_and_pattern_must_both_match() {
+        let filter = KeyFilter::new(Some("jobs_"), Some("*_10_*")).unwrap();
+        assert_eq!(matching(&filter, &KEYS), vec!["jobs_rust_10__false_", "jobs_python_10__false_"]);
+        let filter = KeyFilter::new(Some("videos_"), Some("jobs_*")).unwrap();
+        assert!(matching(&filter, &KEYS).is_empty());
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let filter = KeyFilter::new(None, None).unwrap();
+        assert!(filter.is_empty());
+        assert_eq!(matching(&filter, &KEYS), KEYS.to_vec());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use async_trait::async_trait;
+use super::{inspect, now, CacheBackend, CacheEntry};
+
+/// Process-local cache backed by a `HashMap`. Each instance keeps its own copy.
+///
+/// Values are held as fetched and shared with callers, so hits don't copy
+/// them. The map is bounded by entry count and by an approximate byte size
+/// (key plus serialized value); once either limit is exceeded the least
+/// recently used entries are evicted.
+pub struct MemoryCache {
+    inner: Mutex<Inner>,
+    max_entries: usize,
+    max_bytes: usize,
+}
+
+struct Slot {
+    entry: CacheEntry,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    slots: HashMap<String, Slot>,
+    // Access tick -> key, oldest first.
+    lru: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: usize,
+}
+
+impl Inner {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(slot) = self.slots.get_mut(key) {
+            self.lru.remove(&slot.last_used);
+            slot.last_used = tick;
+            self.lru.insert(tick, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Slot> {
+        let slot = self.slots.remove(key)?;
+        self.lru.remove(&slot.last_used);
+        self.bytes -= slot.entry.size_bytes;
+        Some(slot)
+    }
+
+    fn evict_lru(&mut self) -> bool {
+        let Some((_, key)) = self.lru.pop_first() else {
+            return false;
+        };
+        if let Some(slot) = self.slots.remove(&key) {
+            self.bytes -= slot.entry.size_bytes;
+        }
+        inspect::record_eviction();
+        log::debug!("Evicted least recently used cache entry: {}", key);
+        true
+    }
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        MemoryCache {
+            inner: Mutex::new(Inner::default()),
+            max_entries,
+            max_bytes,
+        }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Option<CacheEntry> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.touch(key);
+        inner.slots.get(key).map(|slot| slot.entry.clone())
+    }
+
+    async fn set(&self, key: &str, entry: CacheEntry) {
+        let size = entry.size_bytes;
+        let mut inner = self.inner.lock().unwrap();
+        // The old value is outdated either way, so it goes even when the new
+        // one is too large to keep.
+        inner.remove(key);
+        if size > self.max_bytes {
+            log::warn!("Not caching {}: entry of {} bytes exceeds the cache size limit", key, size);
+            return;
+        }
+
+        inner.slots.insert(
+            key.to_string(),
+            Slot {
+                entry,
+                last_used: 0,
+            },
+        );
+        inner.bytes += size;
+        inner.touch(key);
+
+        while inner.slots.len() > self.max_entries || inner.bytes > self.max_bytes {
+            if !inner.evict_lru() {
+                break;
+            }
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(key);
+    }
+
+    async fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner = Inner::default();
+    }
+
+    async fn entries(&self) -> Vec<(String, CacheEntry)> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .slots
+            .iter()
+            .map(|(key, slot)| (key.clone(), slot.entry.clone()))
+            .collect()
+    }
+
+    async fn keys(&self) -> Vec<String> {
+        let inner = self.inner.lock().unwrap();
+        inner.slots.keys().cloned().collect()
+    }
+
+    async fn purge_expired(&self) -> usize {
+        let now = now();
+        let mut inner = self.inner.lock().unwrap();
+        let expired: Vec<String> = inner
+            .slots
+            .iter()
+            .filter(|(_, slot)| slot.entry.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            inner.remove(key);
+        }
+        expired.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cache::{EntryKind, Payload};
+
+    fn entry(json: &str) -> CacheEntry {
+        CacheEntry {
+            data: Payload::Json(json.into()),
+            size_bytes: json.len(),
+            kind: EntryKind::Value,
+            timestamp: now(),
+            ttl: 60,
+            grace: 0,
+            origin: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_value_drops_the_old_entry() {
+        let cache = MemoryCache::new(10, 8);
+        cache.set("key", entry("\"old\"")).await;
+        assert!(cache.get("key").await.is_some());
+
+        cache.set("key", entry("\"much too large\"")).await;
+        assert!(cache.get("key").await.is_none());
+        assert_eq!(cache.inner.lock().unwrap().bytes, 0);
+    }
+}
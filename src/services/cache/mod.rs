@@ -0,0 +1,494 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt};
+use lazy_static::lazy_static;
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::ser::{Error as _, Serializer};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::services::error::ServiceError;
+use crate::settings::{self, CacheBackendKind, CacheSettings};
+
+mod inspect;
+mod memory;
+mod redis_store;
+mod singleflight;
+mod snapshot;
+
+pub use inspect::{invalidate, list_keys, stats, CacheKeyInfo, CacheStats, KeyFilter};
+pub use memory::MemoryCache;
+pub use redis_store::RedisCache;
+pub use snapshot::{load_snapshot, save_snapshot, spawn_snapshotter};
+
+lazy_static! {
+    static ref BACKEND: RwLock<Arc<dyn CacheBackend>> = {
+        let defaults = CacheSettings::default();
+        RwLock::new(Arc::new(MemoryCache::new(defaults.max_entries, defaults.max_bytes)))
+    };
+    // Key -> Unix time its last background refresh failed. Stale hits don't
+    // start another refresh until `ttl.error` seconds have passed, so a failing
+    // upstream isn't called on every request during the stale grace period.
+    static ref REFRESH_FAILED: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// What a cache entry records. Empty and error entries are negative entries:
+/// they are kept briefly so repeated requests for a query with no results, or
+/// for an upstream that is failing, don't all go upstream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    #[default]
+    Value,
+    Empty,
+    /// `data` holds the `ServiceError` the fetch failed with.
+    Error,
+}
+
+/// How `get_or_fetch` answered a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheStatus {
+    /// Served from a fresh cache entry.
+    Hit,
+    /// Served from an entry past its soft TTL while it is refreshed.
+    Stale,
+    /// Fetched from upstream for this request.
+    Miss,
+}
+
+/// A value returned by `get_or_fetch`, with where it came from.
+#[derive(Debug)]
+pub struct Cached<T> {
+    pub value: Arc<T>,
+    pub status: CacheStatus,
+    /// Unix time the value was fetched from upstream.
+    pub fetched_at: u64,
+}
+
+impl<T> Cached<T> {
+    /// Wraps a value that was just fetched and never cached, such as a
+    /// fallback.
+    pub fn fresh(value: T) -> Self {
+        Cached {
+            value: Arc::new(value),
+            status: CacheStatus::Miss,
+            fetched_at: now(),
+        }
+    }
+
+    /// Seconds since the value was fetched.
+    pub fn age(&self) -> u64 {
+        now().saturating_sub(self.fetched_at)
+    }
+}
+
+/// A cacheable value with its concrete type erased, so backends can hold it
+/// without knowing `T` and still serialize it when they need to.
+pub trait CacheValue: Any + Send + Sync {
+    fn to_json(&self) -> serde_json::Result<String>;
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+impl<T: Serialize + Send + Sync + 'static> CacheValue for T {
+    fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+/// Whether a fetched value is an empty result, cached as a negative entry
+/// with the shorter `ttl.empty`. Values that are never empty keep the default.
+pub trait IsEmpty {
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl<T> IsEmpty for Vec<T> {
+    fn is_empty(&self) -> bool {
+        <[T]>::is_empty(self)
+    }
+}
+
+/// The cached value. The in-memory backend keeps the fetched value as is, so a
+/// hit is an `Arc` clone; entries read back from Redis or a snapshot arrive as
+/// JSON and are deserialized on each hit. Either way it is stored and
+/// transferred as a JSON string.
+#[derive(Clone)]
+pub enum Payload {
+    Typed(Arc<dyn CacheValue>),
+    Json(Arc<str>),
+}
+
+impl Payload {
+    fn to_json(&self) -> serde_json::Result<Cow<'_, str>> {
+        match self {
+            Payload::Typed(value) => value.to_json().map(Cow::Owned),
+            Payload::Json(json) => Ok(Cow::Borrowed(json)),
+        }
+    }
+
+    fn decode<T: DeserializeOwned + Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        match self {
+            Payload::Typed(value) => value.clone().into_any().downcast::<T>().ok(),
+            Payload::Json(json) => serde_json::from_str(json).ok().map(Arc::new),
+        }
+    }
+}
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_json().map_err(S::Error::custom)?)
+    }
+}
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|json| Payload::Json(json.into()))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub data: Payload,
+    /// Key plus serialized value, measured when the entry was stored.
+    pub size_bytes: usize,
+    #[serde(default)]
+    pub kind: EntryKind,
+    pub timestamp: u64,
+    /// Soft lifetime in seconds, counted from `timestamp`. Past it the entry
+    /// is stale: still served, but refreshed in the background.
+    pub ttl: u64,
+    /// Seconds past `ttl` during which a stale entry may still be served.
+    pub grace: u64,
+    /// The request parameters that produced `data`, so the entry can be
+    /// re-fetched on demand (see `services::refresh_service`).
+    #[serde(default)]
+    pub origin: Option<serde_json::Value>,
+}
+
+impl CacheEntry {
+    pub fn is_stale(&self, now: u64) -> bool {
+        now.saturating_sub(self.timestamp) >= self.ttl
+    }
+
+    /// Whether the entry is past its hard TTL and must not be served at all.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now.saturating_sub(self.timestamp) >= self.ttl + self.grace
+    }
+}
+
+/// Storage behind the `get_or_fetch`/`set_cache` helpers. Backends only store
+/// entries; expiry is decided here from `CacheEntry::timestamp` and `ttl`.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    async fn get(&self, key: &str) -> Option<CacheEntry>;
+    async fn set(&self, key: &str, entry: CacheEntry);
+    async fn remove(&self, key: &str);
+    async fn clear(&self);
+
+    /// Every stored entry, including expired ones not yet purged.
+    async fn entries(&self) -> Vec<(String, CacheEntry)>;
+
+    /// The keys of `entries`, without reading their values.
+    async fn keys(&self) -> Vec<String>;
+
+    /// Drops expired entries and returns how many were removed. Backends with
+    /// native expiry (Redis) have nothing to do here.
+    async fn purge_expired(&self) -> usize {
+        0
+    }
+}
+
+/// Selects the cache backend for the process. Must run before the server
+/// starts handling requests; the in-memory backend is used until then.
+pub async fn init(settings: &CacheSettings) -> redis::RedisResult<()> {
+    let backend: Arc<dyn CacheBackend> = match settings.backend {
+        CacheBackendKind::Memory => {
+            Arc::new(MemoryCache::new(settings.max_entries, settings.max_bytes))
+        }
+        CacheBackendKind::Redis => {
+            Arc::new(RedisCache::connect(&settings.redis_url, &settings.redis_prefix).await?)
+        }
+    };
+    *BACKEND.write().unwrap() = backend;
+    log::info!("Using {:?} cache backend", settings.backend);
+    Ok(())
+}
+
+fn backend() -> Arc<dyn CacheBackend> {
+    BACKEND.read().unwrap().clone()
+}
+
+/// Spawns a background task that purges expired entries every `interval`. A
+/// zero interval disables the sweeper; expired entries are then only dropped
+/// when they are read or evicted.
+pub fn spawn_sweeper(interval: Duration) {
+    if interval.is_zero() {
+        log::info!("Cache sweeper disabled");
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let retry_after = settings::get().cache.ttl.error;
+            let now = now();
+            REFRESH_FAILED
+                .lock()
+                .unwrap()
+                .retain(|_, failed_at| now.saturating_sub(*failed_at) < retry_after);
+            let purged = backend().purge_expired().await;
+            if purged > 0 {
+                log::info!("Cache sweeper purged {} expired entries", purged);
+            }
+        }
+    });
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Serves `key` from the cache, calling `fetch` on a miss. `origin` is stored
+/// with the entry so it can be refreshed later without the original caller.
+///
+/// Entries past their soft TTL but still inside the stale grace period are
+/// returned immediately while `fetch` refreshes them in a background task; only
+/// missing or hard-expired entries make the caller wait for `fetch`. Concurrent
+/// misses and refreshes for the same key share a single `fetch`. After a failed
+/// refresh the stale entry is served without retrying for `ttl.error` seconds.
+pub async fn get_or_fetch<T, O, F, Fut>(key: &str, origin: &O, fetch: F) -> Result<Cached<T>, ServiceError>
+where
+    T: Serialize + DeserializeOwned + IsEmpty + Send + Sync + 'static,
+    O: Serialize,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ServiceError>> + Send + 'static,
+{
+    if let Some(entry) = backend().get(key).await {
+        let now = now();
+        if !entry.is_expired(now) {
+            if entry.kind == EntryKind::Error {
+                inspect::record_negative_hit();
+                log::info!("Serving cached upstream error for {}", key);
+                let error = entry.data.decode::<ServiceError>().map_or_else(
+                    || ServiceError::Upstream("Upstream request failed".to_string()),
+                    |error| error.as_ref().clone(),
+                );
+                return Err(error);
+            }
+
+            let stale = entry.is_stale(now);
+            if let Some(value) = entry.data.decode::<T>() {
+                if entry.kind == EntryKind::Empty {
+                    inspect::record_negative_hit();
+                } else {
+                    inspect::record_hit(stale);
+                }
+                let status = if stale {
+                    if refresh_failed_recently(key, now) {
+                        log::info!("Serving stale cache entry for {}; its last refresh failed", key);
+                    } else {
+                        log::info!("Serving stale cache entry for {} while refreshing", key);
+                        singleflight::spawn(key, fetch_and_store(key, origin, fetch, false));
+                    }
+                    CacheStatus::Stale
+                } else {
+                    log::info!("Serving cached data for {}", key);
+                    CacheStatus::Hit
+                };
+                return Ok(Cached {
+                    value,
+                    status,
+                    fetched_at: entry.timestamp,
+                });
+            }
+        }
+    }
+
+    inspect::record_miss();
+    let value = singleflight::coalesce(key, fetch_and_store(key, origin, fetch, true)).await?;
+    Ok(Cached {
+        value,
+        status: CacheStatus::Miss,
+        fetched_at: now(),
+    })
+}
+
+/// Re-runs `fetch` for `key` regardless of what is cached and stores the
+/// result. Joins a fetch for the same key if one is already running. A failed
+/// fetch leaves the existing entry in place.
+pub async fn refresh<T, O, F, Fut>(key: &str, origin: &O, fetch: F) -> Result<Arc<T>, ServiceError>
+where
+    T: Serialize + IsEmpty + Send + Sync + 'static,
+    O: Serialize,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ServiceError>> + Send + 'static,
+{
+    singleflight::coalesce(key, fetch_and_store(key, origin, fetch, false)).await
+}
+
+fn refresh_failed_recently(key: &str, now: u64) -> bool {
+    let retry_after = settings::get().cache.ttl.error;
+    let mut failed = REFRESH_FAILED.lock().unwrap();
+    match failed.get(key) {
+        Some(failed_at) if now.saturating_sub(*failed_at) < retry_after => true,
+        Some(_) => {
+            failed.remove(key);
+            false
+        }
+        None => false,
+    }
+}
+
+/// The request parameters stored with `key`, if it is cached.
+pub async fn origin(key: &str) -> Option<serde_json::Value> {
+    backend().get(key).await.and_then(|entry| entry.origin)
+}
+
+// Errors are only cached when `cache_errors` is set, i.e. when there is no
+// usable entry to keep serving; a failed refresh must not replace good data,
+// and is recorded in `REFRESH_FAILED` instead.
+fn fetch_and_store<T, O, F, Fut>(
+    key: &str,
+    origin: &O,
+    fetch: F,
+    cache_errors: bool,
+) -> impl FnOnce() -> BoxFuture<'static, Result<Arc<T>, ServiceError>> + Send + 'static
+where
+    T: Serialize + IsEmpty + Send + Sync + 'static,
+    O: Serialize,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ServiceError>> + Send + 'static,
+{
+    let key = key.to_string();
+    let origin = serde_json::to_value(origin).ok();
+    move || {
+        async move {
+            match fetch().await {
+                Ok(value) => {
+                    let value = Arc::new(value);
+                    set_cache(&key, value.clone(), origin).await;
+                    REFRESH_FAILED.lock().unwrap().remove(&key);
+                    Ok(value)
+                }
+                Err(e) => {
+                    if cache_errors {
+                        store(&key, Arc::new(e.clone()), EntryKind::Error, origin).await;
+                    } else {
+                        REFRESH_FAILED.lock().unwrap().insert(key, now());
+                    }
+                    Err(e)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Caches `data` with the TTL configured for the key's namespace (the part
+/// before the first `_`, e.g. `videos` for `videos_rust_5_relevance`). Empty
+/// results are cached as negative entries with the shorter `ttl.empty`.
+pub async fn set_cache<T: CacheValue + IsEmpty>(key: &str, data: Arc<T>, origin: Option<serde_json::Value>) {
+    let kind = if data.is_empty() {
+        EntryKind::Empty
+    } else {
+        EntryKind::Value
+    };
+    store(key, data, kind, origin).await;
+}
+
+async fn store<T: CacheValue>(key: &str, data: Arc<T>, kind: EntryKind, origin: Option<serde_json::Value>) {
+    let json_len = match data.to_json() {
+        Ok(json) => json.len(),
+        Err(e) => {
+            log::warn!("Not caching {}: failed to serialize value: {}", key, e);
+            return;
+        }
+    };
+    let cache = &settings::get().cache;
+    let (ttl, grace) = match kind {
+        EntryKind::Value => (cache.ttl.for_key(key).as_secs(), cache.stale_grace_secs),
+        EntryKind::Empty => (cache.ttl.empty, 0),
+        EntryKind::Error => (cache.ttl.error, 0),
+    };
+    if kind != EntryKind::Value {
+        log::info!("Caching {:?} result for {} for {} seconds", kind, key, ttl);
+    }
+    backend()
+        .set(
+            key,
+            CacheEntry {
+                data: Payload::Typed(data),
+                size_bytes: key.len() + json_len,
+                kind,
+                timestamp: now(),
+                ttl,
+                grace,
+                origin,
+            },
+        )
+        .await;
+}
+
+pub async fn clear_cache() {
+    backend().clear().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn stale_entry(key: &str) {
+        backend()
+            .set(
+                key,
+                CacheEntry {
+                    data: Payload::Typed(Arc::new(vec![1u32])),
+                    size_bytes: key.len() + 3,
+                    kind: EntryKind::Value,
+                    timestamp: now() - 100,
+                    ttl: 10,
+                    grace: 3600,
+                    origin: None,
+                },
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn failed_stale_refresh_is_not_retried_on_the_next_hit() {
+        let key = "test_failing_refresh";
+        stale_entry(key).await;
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..5 {
+            let counter = calls.clone();
+            let cached: Cached<Vec<u32>> = get_or_fetch(key, &(), move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err(ServiceError::RateLimited("429".to_string()))
+            })
+            .await
+            .unwrap();
+            assert_eq!(cached.status, CacheStatus::Stale);
+            assert_eq!(*cached.value, vec![1]);
+            // Let the background refresh finish before the next request.
+            while REFRESH_FAILED.lock().unwrap().get(key).is_none() {
+                tokio::task::yield_now().await;
+            }
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, RedisResult};
+use super::{CacheBackend, CacheEntry};
+
+/// Redis-backed cache shared by every instance pointing at the same server.
+/// Keys are namespaced with `prefix` so `clear` never touches foreign data.
+pub struct RedisCache {
+    conn: MultiplexedConnection,
+    prefix: String,
+}
+
+impl RedisCache {
+    pub async fn connect(url: &str, prefix: &str) -> RedisResult<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = client.get_multiplexed_tokio_connection().await?;
+        log::info!("Connected to Redis cache at {}", url);
+        Ok(RedisCache {
+            conn,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    async fn scan_keys(&self) -> RedisResult<Vec<String>> {
+        let mut conn = self.conn.clone();
+        let mut iter = conn.scan_match::<_, String>(format!("{}*", self.prefix)).await?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedisCache {
+    async fn get(&self, key: &str) -> Option<CacheEntry> {
+        let mut conn = self.conn.clone();
+        match conn.get::<_, Option<String>>(self.redis_key(key)).await {
+            Ok(raw) => raw.and_then(|raw| serde_json::from_str(&raw).ok()),
+            Err(e) => {
+                log::warn!("Redis GET failed for key {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, entry: CacheEntry) {
+        let ttl = (entry.ttl + entry.grace).max(1) as usize;
+        let raw = match serde_json::to_string(&entry) {
+            Ok(raw) => raw,
+            Err(e) => {
+                log::warn!("Failed to serialize cache entry {}: {}", key, e);
+                return;
+            }
+        };
+        let mut conn = self.conn.clone();
+        let result: RedisResult<()> = conn
+            .set_ex(self.redis_key(key), raw, ttl)
+            .await;
+        if let Err(e) = result {
+            log::warn!("Redis SET failed for key {}: {}", key, e);
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        let mut conn = self.conn.clone();
+        let result: RedisResult<()> = conn.del(self.redis_key(key)).await;
+        if let Err(e) = result {
+            log::warn!("Redis DEL failed for key {}: {}", key, e);
+        }
+    }
+
+    async fn clear(&self) {
+        let keys = match self.scan_keys().await {
+            Ok(keys) => keys,
+            Err(e) => {
+                log::warn!("Redis SCAN failed while clearing cache: {}", e);
+                return;
+            }
+        };
+        if keys.is_empty() {
+            return;
+        }
+        let mut conn = self.conn.clone();
+        let result: RedisResult<()> = conn.del(keys).await;
+        if let Err(e) = result {
+            log::warn!("Redis DEL failed while clearing cache: {}", e);
+        }
+    }
+
+    async fn entries(&self) -> Vec<(String, CacheEntry)> {
+        let keys = match self.scan_keys().await {
+            Ok(keys) if !keys.is_empty() => keys,
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                log::warn!("Redis SCAN failed while listing cache: {}", e);
+                return Vec::new();
+            }
+        };
+        let mut conn = self.conn.clone();
+        // MGET always returns a list, even for a single key.
+        let values: Vec<Option<String>> = match redis::cmd("MGET").arg(&keys).query_async(&mut conn).await {
+            Ok(values) => values,
+            Err(e) => {
+                log::warn!("Redis MGET failed while listing cache: {}", e);
+                return Vec::new();
+            }
+        };
+        keys.into_iter()
+            .zip(values)
+            .filter_map(|(key, raw)| {
+                let entry = serde_json::from_str(&raw?).ok()?;
+                Some((key[self.prefix.len()..].to_string(), entry))
+            })
+            .collect()
+    }
+
+    async fn keys(&self) -> Vec<String> {
+        match self.scan_keys().await {
+            Ok(keys) => keys.into_iter().map(|key| key[self.prefix.len()..].to_string()).collect(),
+            Err(e) => {
+                log::warn!("Redis SCAN failed while listing cache keys: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
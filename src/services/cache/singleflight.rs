@@ -0,0 +1,69 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use lazy_static::lazy_static;
+use crate::services::error::ServiceError;
+
+type Flight<T> = Shared<BoxFuture<'static, Result<T, ServiceError>>>;
+
+lazy_static! {
+    static ref IN_FLIGHT: Mutex<HashMap<String, Box<dyn Any + Send>>> = Mutex::new(HashMap::new());
+}
+
+/// Runs `fetch` once per `key` at a time: callers arriving while a fetch for
+/// the same key is running wait for it and share its result instead of
+/// starting their own.
+///
+/// The fetch runs in its own task, so it completes (and can populate the
+/// cache) even if the caller that started it goes away.
+pub async fn coalesce<T, F, Fut>(key: &str, fetch: F) -> Result<T, ServiceError>
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ServiceError>> + Send + 'static,
+{
+    join(key, fetch).await
+}
+
+/// Starts (or joins) the flight for `key` without waiting for it.
+pub fn spawn<T, F, Fut>(key: &str, fetch: F)
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ServiceError>> + Send + 'static,
+{
+    drop(join(key, fetch));
+}
+
+fn join<T, F, Fut>(key: &str, fetch: F) -> Flight<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ServiceError>> + Send + 'static,
+{
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    if let Some(flight) = in_flight.get(key).and_then(|f| f.downcast_ref::<Flight<T>>()) {
+        log::info!("Joining in-flight fetch for {}", key);
+        return flight.clone();
+    }
+
+    let owned_key = key.to_string();
+    // The task can't remove itself from the map before the insert below,
+    // since it needs the lock we are holding.
+    let handle = tokio::spawn(async move {
+        let result = fetch().await;
+        IN_FLIGHT.lock().unwrap().remove(&owned_key);
+        result
+    });
+    let flight: Flight<T> = async move {
+        handle
+            .await
+            .unwrap_or_else(|e| Err(ServiceError::Upstream(format!("Fetch task failed: {}", e))))
+    }
+    .boxed()
+    .shared();
+    in_flight.insert(key.to_string(), Box::new(flight.clone()));
+    flight
+}
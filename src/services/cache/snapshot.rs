@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use super::{backend, now, CacheEntry};
+
+/// Writes every live cache entry to `path` as JSON and returns how many were
+/// written. Expired entries the sweeper hasn't purged yet are left out. The
+/// file is replaced atomically so a crash mid-write never leaves a truncated
+/// snapshot behind.
+pub async fn save_snapshot(path: &Path) -> io::Result<usize> {
+    let now = now();
+    let entries: HashMap<String, CacheEntry> = backend()
+        .entries()
+        .await
+        .into_iter()
+        .filter(|(_, entry)| !entry.is_expired(now))
+        .collect();
+    let json = serde_json::to_vec(&entries)?;
+
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, json).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(entries.len())
+}
+
+/// Loads a snapshot written by `save_snapshot`, skipping entries that expired
+/// while the server was down, and returns how many were restored. A missing
+/// file is not an error.
+pub async fn load_snapshot(path: &Path) -> io::Result<usize> {
+    let json = match tokio::fs::read(path).await {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let entries: HashMap<String, CacheEntry> = serde_json::from_slice(&json)?;
+
+    let now = now();
+    let backend = backend();
+    let mut restored = 0;
+    for (key, entry) in entries {
+        if entry.is_expired(now) {
+            continue;
+        }
+        backend.set(&key, entry).await;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+/// Spawns a background task that writes a snapshot to `path` every `interval`.
+/// A zero interval disables periodic snapshots; one is still written on
+/// shutdown.
+pub fn spawn_snapshotter(path: PathBuf, interval: Duration) {
+    if interval.is_zero() {
+        log::info!("Periodic cache snapshots disabled");
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately; there is nothing worth saving yet.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match save_snapshot(&path).await {
+                Ok(count) => log::debug!("Wrote cache snapshot with {} entries to {:?}", count, path),
+                Err(e) => log::warn!("Failed to write cache snapshot to {:?}: {}", path, e),
+            }
+        }
+    });
+}
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use atom_syndication::extension::{Extension, ExtensionMap};
+use atom_syndication::{Entry, Feed};
+use futures::future::join_all;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+use crate::services::cache::{self, Cached};
+use crate::services::error::ServiceError;
+use crate::services::youtube_service::{determine_difficulty, youtube_client, Video};
+use crate::settings;
+
+lazy_static! {
+    static ref CHANNEL_ID: Regex = Regex::new(r"^UC[A-Za-z0-9_-]{22}$").unwrap();
+}
+
+// Most channels one request may read.
+const MAX_CHANNELS: usize = 50;
+
+/// Latest videos of the given channels, newest first, optionally only those
+/// whose title or description contains every word of `query`. Without
+/// channel IDs the configured curated channels are used.
+///
+/// Each channel's feed is cached on its own, so the result is reported with
+/// the cache status and age of the oldest one. Channels whose feed fails are
+/// left out unless every one fails.
+pub async fn handle_channel_videos(
+    channel_ids: &[String],
+    query: Option<&str>,
+    limit: u32,
+) -> Result<Cached<Vec<Video>>, ServiceError> {
+    if limit == 0 {
+        return Err(ServiceError::InvalidParams("limit must be at least 1".to_string()));
+    }
+    let channel_ids = if channel_ids.is_empty() {
+        &settings::get().channels.curated
+    } else {
+        channel_ids
+    };
+    if channel_ids.is_empty() {
+        return Err(ServiceError::InvalidParams(
+            "channel_ids is required when no curated channels are configured".to_string(),
+        ));
+    }
+    if channel_ids.len() > MAX_CHANNELS {
+        return Err(ServiceError::InvalidParams(format!("at most {} channels can be read at once", MAX_CHANNELS)));
+    }
+    for channel_id in channel_ids {
+        validate_channel_id(channel_id)?;
+    }
+
+    let mut feeds = Vec::new();
+    let mut first_error = None;
+    let results = join_all(channel_ids.iter().map(|id| channel_feed(id))).await;
+    for (channel_id, result) in channel_ids.iter().zip(results) {
+        match result {
+            Ok(feed) => feeds.push(feed),
+            Err(e) => {
+                log::warn!("Failed to read feed of channel {}: {}", channel_id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    let Some(oldest) = feeds.iter().min_by_key(|feed| feed.fetched_at) else {
+        return Err(first_error.unwrap_or_else(|| ServiceError::Upstream("No channel feeds".to_string())));
+    };
+    let (status, fetched_at) = (oldest.status, oldest.fetched_at);
+
+    let words: Vec<String> = query
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let mut videos: Vec<Video> = feeds
+        .iter()
+        .flat_map(|feed| feed.value.iter())
+        .filter(|video| {
+            let text = format!("{} {}", video.title, video.description).to_lowercase();
+            words.iter().all(|word| text.contains(word.as_str()))
+        })
+        .cloned()
+        .collect();
+    // Feed timestamps are RFC 3339 in UTC, so they sort as text.
+    videos.sort_by(|a, b| b.published_text.cmp(&a.published_text));
+    videos.truncate(limit as usize);
+
+    Ok(Cached {
+        value: Arc::new(videos),
+        status,
+        fetched_at,
+    })
+}
+
+/// Re-downloads a channel feed from the channel ID stored with its cache entry
+/// and returns how many videos it lists.
+pub async fn refresh_channel_feed(origin: Value) -> Result<usize, ServiceError> {
+    let channel_id: String = serde_json::from_value(origin)?;
+    validate_channel_id(&channel_id)?;
+    let key = cache_key(&channel_id);
+    let origin = channel_id.clone();
+    let videos = cache::refresh(&key, &origin, move || async move { fetch_channel_feed(&channel_id).await }).await?;
+    Ok(videos.len())
+}
+
+fn validate_channel_id(channel_id: &str) -> Result<(), ServiceError> {
+    if CHANNEL_ID.is_match(channel_id) {
+        Ok(())
+    } else {
+        Err(ServiceError::InvalidParams(format!("invalid channel ID: {}", channel_id)))
+    }
+}
+
+fn cache_key(channel_id: &str) -> String {
+    format!("channel_{}", channel_id)
+}
+
+async fn channel_feed(channel_id: &str) -> Result<Cached<Vec<Video>>, ServiceError> {
+    let owned_id = channel_id.to_string();
+    cache::get_or_fetch(&cache_key(channel_id), &channel_id, move || async move {
+        fetch_channel_feed(&owned_id).await
+    })
+    .await
+}
+
+/// Reads a channel's Atom feed, which lists its 15 latest uploads. Far more
+/// stable than the HTML pages, but without durations.
+async fn fetch_channel_feed(channel_id: &str) -> Result<Vec<Video>, ServiceError> {
+    let url = format!("https://www.youtube.com/feeds/videos.xml?channel_id={}", channel_id);
+    log::info!("Fetching YouTube channel feed: {}", url);
+
+    let response = youtube_client()?.get(&url).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(ServiceError::NotFound(format!("channel {}", channel_id)));
+    }
+    if !response.status().is_success() {
+        return Err(ServiceError::from_status("YouTube", response.status()));
+    }
+    let body = response.bytes().await?;
+    let feed = Feed::read_from(&body[..]).map_err(|e| ServiceError::Parse(format!("Invalid channel feed: {}", e)))?;
+
+    let videos: Vec<Video> = feed.entries().iter().filter_map(parse_feed_entry).collect();
+    log::info!("Fetched {} videos from channel {}", videos.len(), channel_id);
+    Ok(videos)
+}
+
+fn parse_feed_entry(entry: &Entry) -> Option<Video> {
+    let extensions = entry.extensions();
+    let video_id = extension(extensions, "yt", "videoId")
+        .and_then(|e| e.value())
+        .or_else(|| entry.id().strip_prefix("yt:video:"))
+        .filter(|id| !id.is_empty())?;
+    let title = entry.title().as_str().to_string();
+    if title.is_empty() {
+        return None;
+    }
+    let media = extension(extensions, "media", "group").map(|group| group.children());
+    let media_child = |name: &str| media.and_then(|children| children.get(name)).and_then(|c| c.first());
+    let published = entry.published().unwrap_or(entry.updated()).to_utc();
+
+    Some(Video {
+        url: format!("https://www.youtube.com/watch?v={}", video_id),
+        video_id: video_id.to_string(),
+        r#type: "video".to_string(),
+        free: true,
+        image: media_child("thumbnail")
+            .and_then(|thumbnail| thumbnail.attrs().get("url"))
+            .cloned()
+            .unwrap_or_default(),
+        source: "YouTube".to_string(),
+        difficulty: determine_difficulty(&title),
+        title,
+        description: media_child("description")
+            .and_then(|description| description.value())
+            .unwrap_or_default()
+            .to_string(),
+        channel_name: entry.authors().first().map(|author| author.name().to_string()),
+        channel_id: extension(extensions, "yt", "channelId")
+            .and_then(|e| e.value())
+            .map(|id| id.to_string()),
+        duration_secs: None,
+        view_count: media_child("community")
+            .and_then(|community| community.children().get("statistics"))
+            .and_then(|statistics| statistics.first())
+            .and_then(|statistics| statistics.attrs().get("views"))
+            .and_then(|views| views.parse().ok()),
+        published_text: Some(published.to_rfc3339()),
+        published_date: Some(published.format("%Y-%m-%d").to_string()),
+        is_short: entry.links().iter().any(|link| link.href().contains("/shorts/")),
+        is_live: false,
+        is_upcoming: false,
+        playlist_id: None,
+        video_count: None,
+    })
+}
+
+fn extension<'a>(extensions: &'a ExtensionMap, prefix: &str, name: &str) -> Option<&'a Extension> {
+    extensions.get(prefix)?.get(name)?.first()
+}
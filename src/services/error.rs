@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors returned by the scraper services. `Clone` so a single upstream
+/// failure can be shared by every request waiting on the same fetch, and
+/// serializable so it can be cached as a negative entry.
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum ServiceError {
+    /// The upstream could not be reached or answered with an error status.
+    #[error("Upstream request failed: {0}")]
+    Upstream(String),
+    /// The upstream answered, but not in the shape we expect; usually means
+    /// the page or API changed.
+    #[error("Unexpected upstream response: {0}")]
+    Parse(String),
+    #[error("Upstream request timed out: {0}")]
+    Timeout(String),
+    #[error("Rate limited by upstream: {0}")]
+    RateLimited(String),
+    #[error("Invalid parameter: {0}")]
+    InvalidParams(String),
+    /// The upstream says the requested item doesn't exist or isn't available.
+    #[error("Not found: {0}")]
+    NotFound(String),
+}
+
+impl ServiceError {
+    /// Machine-readable code used in error response bodies.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServiceError::Upstream(_) => "upstream_error",
+            ServiceError::Parse(_) => "upstream_parse_error",
+            ServiceError::Timeout(_) => "upstream_timeout",
+            ServiceError::RateLimited(_) => "rate_limited",
+            ServiceError::InvalidParams(_) => "invalid_params",
+            ServiceError::NotFound(_) => "not_found",
+        }
+    }
+
+    /// Maps a non-success upstream status to an error.
+    pub fn from_status(upstream: &str, status: reqwest::StatusCode) -> Self {
+        let message = format!("{} returned status {}", upstream, status);
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            ServiceError::RateLimited(message)
+        } else {
+            ServiceError::Upstream(message)
+        }
+    }
+}
+
+/// Rejects search parameters no upstream query can satisfy.
+pub fn validate_search(query: &str, limit: u32) -> Result<(), ServiceError> {
+    if query.trim().is_empty() {
+        return Err(ServiceError::InvalidParams("query must not be empty".to_string()));
+    }
+    if limit == 0 {
+        return Err(ServiceError::InvalidParams("limit must be at least 1".to_string()));
+    }
+    Ok(())
+}
+
+impl From<reqwest::Error> for ServiceError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ServiceError::Timeout(e.to_string())
+        } else if e.is_decode() {
+            ServiceError::Parse(e.to_string())
+        } else if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+            ServiceError::RateLimited(e.to_string())
+        } else {
+            ServiceError::Upstream(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ServiceError {
+    fn from(e: serde_json::Error) -> Self {
+        ServiceError::Parse(e.to_string())
+    }
+}
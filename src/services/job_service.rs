@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use std::error::Error;
+use std::sync::Arc;
 use log::{info, warn};
 use tokio::time::Duration;
 use regex::Regex;
-use crate::services::cache;
+use crate::services::cache::{self, Cached};
+use crate::services::error::{validate_search, ServiceError};
 
 #[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct Job {
@@ -23,120 +24,179 @@ pub struct Job {
     pub employer_logo: Option<String>,
 }
 
+/// Job search results, filtered from the shared RemoteOK feed on every
+/// request. Results aren't cached on their own, so they are never older than
+/// the feed; `status` and `fetched_at` describe the feed.
 pub async fn handle_job_scraper(
     query: &str,
     limit: u32,
     location: &str,
     remote_only: Option<bool>,
     job_type: Option<&str>,
-) -> Result<Vec<Job>, Box<dyn Error>> {
-    let remote_flag = remote_only.unwrap_or(false);
-    let is_trending = query.to_lowercase().starts_with("trending:") || query.to_lowercase().starts_with("trending ");
-    let clean_query = if is_trending {
-        if query.to_lowercase().starts_with("trending:") {
-            query.trim_start_matches("trending:").trim()
+) -> Result<Cached<Vec<Job>>, ServiceError> {
+    validate_search(query, limit)?;
+    let search = JobSearch::new(query, limit, location, remote_only, job_type);
+    let feed = remoteok_feed().await?;
+    Ok(Cached {
+        value: Arc::new(search.filter(&feed.value)),
+        status: feed.status,
+        fetched_at: feed.fetched_at,
+    })
+}
+
+/// Job search parameters, with the `trending` prefix split off the query.
+#[derive(Debug)]
+struct JobSearch {
+    query: String,
+    clean_query: String,
+    is_trending: bool,
+    limit: u32,
+    location: String,
+    remote_only: bool,
+    job_type: Option<String>,
+}
+
+impl JobSearch {
+    fn new(
+        query: &str,
+        limit: u32,
+        location: &str,
+        remote_only: Option<bool>,
+        job_type: Option<&str>,
+    ) -> Self {
+        let is_trending = query.to_lowercase().starts_with("trending:") || query.to_lowercase().starts_with("trending ");
+        let clean_query = if is_trending {
+            if query.to_lowercase().starts_with("trending:") {
+                query.trim_start_matches("trending:").trim()
+            } else {
+                query.trim_start_matches("trending ").trim()
+            }
         } else {
-            query.trim_start_matches("trending ").trim()
+            query
+        };
+
+        JobSearch {
+            query: query.to_string(),
+            clean_query: clean_query.to_string(),
+            is_trending,
+            limit,
+            location: location.to_string(),
+            remote_only: remote_only.unwrap_or(false),
+            job_type: job_type.map(|jt| jt.to_string()),
         }
-    } else {
-        query
-    };
-
-    let cache_key = format!(
-        "jobs_{}_{}_{}_{}_{}",
-        clean_query.to_lowercase().replace(" ", "_"),
-        limit,
-        location.to_lowercase().replace(" ", "_"),
-        remote_flag,
-        job_type.unwrap_or("").to_lowercase().replace(" ", "_")
-    );
-
-    if let Some(jobs) = cache::get_cache::<Vec<Job>>(&cache_key) {
+    }
+
+    fn filter(&self, feed: &[serde_json::Value]) -> Vec<Job> {
+        let query = self.query.as_str();
+        let limit = self.limit;
+        let location = self.location.as_str();
+        let remote_flag = self.remote_only;
+        let job_type = self.job_type.as_deref();
+        let is_trending = self.is_trending;
+
         info!(
-            "Using cached job data for: {} (limit: {}, location: {}, remote_only: {}, job_type: {:?})",
+            "Filtering RemoteOK feed for: {} (limit: {}, location: {}, remote_only: {}, job_type: {:?})",
             query, limit, location, remote_flag, job_type
         );
-        return Ok(jobs);
-    }
 
-    info!(
-        "Fetching fresh job data for: {} (limit: {}, location: {}, remote_only: {}, job_type: {:?})",
-        query, limit, location, remote_flag, job_type
-    );
+        let mut jobs = Vec::new();
 
-    let mut jobs = Vec::new();
+        if !location.is_empty() {
+            let location_jobs = filter_remoteok_jobs_with_location(feed, &self.clean_query, limit, location, job_type);
+            info!("Found {} jobs for location: {}", location_jobs.len(), location);
+            jobs.extend(location_jobs);
+        }
 
-    if !location.is_empty() {
-        match fetch_remoteok_jobs_with_location(clean_query, limit, location, job_type).await {
-            Ok(location_jobs) => {
-                info!("Found {} jobs for location: {}", location_jobs.len(), location);
-                jobs.extend(location_jobs);
-            }
-            Err(e) => warn!("Location search failed: {}", e),
+        if remote_flag || jobs.len() < limit as usize || is_trending {
+            let remaining = limit as usize - jobs.len();
+            let remote_jobs = filter_remoteok_jobs(feed, &self.clean_query, remaining as u32, job_type, is_trending);
+            let modified_remote_jobs = if !location.is_empty() && jobs.is_empty() {
+                remote_jobs
+                    .into_iter()
+                    .map(|mut job| {
+                        job.location = format!("Remote (Worldwide, including {})", location);
+                        job
+                    })
+                    .collect()
+            } else {
+                remote_jobs
+            };
+
+            // For trending searches, sort by recency
+            let sorted_jobs = if is_trending {
+                let mut jobs_with_date: Vec<(Job, Option<DateTime<Utc>>)> = modified_remote_jobs
+                    .into_iter()
+                    .map(|job| {
+                        let date = job.date_posted.as_ref()
+                            .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+                            .map(|d| d.with_timezone(&Utc));
+                        (job, date)
+                    })
+                    .collect();
+                jobs_with_date.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.title.cmp(&b.0.title))); // Secondary sort by title for stability
+                jobs_with_date.into_iter().map(|(job, _)| job).collect()
+            } else {
+                modified_remote_jobs
+            };
+
+            info!("Found {} additional remote jobs", sorted_jobs.len());
+            jobs.extend(sorted_jobs);
         }
-    }
 
-    if remote_flag || jobs.len() < limit as usize || is_trending {
-        let remaining = limit as usize - jobs.len();
-        match fetch_remoteok_jobs(clean_query, remaining as u32, job_type, is_trending).await {
-            Ok(remote_jobs) => {
-                let modified_remote_jobs = if !location.is_empty() && jobs.is_empty() {
-                    remote_jobs
-                        .into_iter()
-                        .map(|mut job| {
-                            job.location = format!("Remote (Worldwide, including {})", location);
-                            job
-                        })
-                        .collect()
-                } else {
-                    remote_jobs
-                };
-
-                // For trending searches, sort by recency
-                let sorted_jobs = if is_trending {
-                    let mut jobs_with_date: Vec<(Job, Option<DateTime<Utc>>)> = modified_remote_jobs
-                        .into_iter()
-                        .map(|job| {
-                            let date = job.date_posted.as_ref()
-                                .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
-                                .map(|d| d.with_timezone(&Utc));
-                            (job, date)
-                        })
-                        .collect();
-                    jobs_with_date.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.title.cmp(&b.0.title))); // Secondary sort by title for stability
-                    jobs_with_date.into_iter().map(|(job, _)| job).collect()
-                } else {
-                    modified_remote_jobs
-                };
+        jobs.truncate(limit as usize);
 
-                info!("Found {} additional remote jobs", sorted_jobs.len());
-                jobs.extend(sorted_jobs);
-            }
-            Err(e) => warn!("Remote job search failed: {}", e),
+        if jobs.is_empty() {
+            warn!(
+                "No jobs found for query: {} (location: {}, remote_only: {}, job_type: {:?})",
+                query, location, remote_flag, job_type
+            );
         }
+
+        jobs
     }
+}
 
-    jobs.truncate(limit as usize);
+const REMOTEOK_FEED_KEY: &str = "feed_remoteok";
+// Stored as the feed entry's origin. The feed takes no parameters, but a
+// `null` origin reads back as none from Redis or a snapshot, and an entry
+// without one can't be refreshed through the cache endpoints.
+const REMOTEOK_FEED_ORIGIN: &str = "remoteok";
+
+/// The full RemoteOK job feed, shared by every job query through the cache.
+/// Kept hot by `spawn_feed_refresher`; a cold or expired feed is fetched on
+/// demand, once, however many queries are waiting for it.
+async fn remoteok_feed() -> Result<Cached<Vec<serde_json::Value>>, ServiceError> {
+    cache::get_or_fetch(REMOTEOK_FEED_KEY, &REMOTEOK_FEED_ORIGIN, fetch_remoteok_feed).await
+}
 
-    if !jobs.is_empty() {
-        cache::set_cache(&cache_key, &jobs);
-        info!("Cached {} jobs with key: {}", jobs.len(), cache_key);
-    } else {
-        warn!(
-            "No jobs found for query: {} (location: {}, remote_only: {}, job_type: {:?})",
-            query, location, remote_flag, job_type
-        );
-    }
+/// Downloads the RemoteOK feed into the cache, replacing the current snapshot.
+/// Returns the number of jobs in the feed.
+pub async fn refresh_remoteok_feed() -> Result<usize, ServiceError> {
+    let feed: Arc<Vec<serde_json::Value>> = cache::refresh(REMOTEOK_FEED_KEY, &REMOTEOK_FEED_ORIGIN, fetch_remoteok_feed).await?;
+    Ok(feed.len())
+}
 
-    Ok(jobs)
+/// Spawns a background task that re-downloads the RemoteOK feed every
+/// `interval`, so job queries rarely wait on RemoteOK. A zero interval
+/// disables it; the feed is then only fetched when its cache entry expires.
+pub fn spawn_feed_refresher(interval: Duration) {
+    if interval.is_zero() {
+        info!("Background RemoteOK feed refresh disabled");
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match refresh_remoteok_feed().await {
+                Ok(count) => info!("Refreshed RemoteOK feed with {} jobs", count),
+                Err(e) => warn!("Failed to refresh RemoteOK feed: {}", e),
+            }
+        }
+    });
 }
 
-async fn fetch_remoteok_jobs(
-    query: &str,
-    limit: u32,
-    job_type: Option<&str>,
-    is_trending: bool,
-) -> Result<Vec<Job>, Box<dyn Error>> {
+async fn fetch_remoteok_feed() -> Result<Vec<serde_json::Value>, ServiceError> {
     let api_url = "https://remoteok.io/api";
 
     let client = reqwest::Client::builder()
@@ -147,23 +207,36 @@ async fn fetch_remoteok_jobs(
     let response = client.get(api_url).send().await?;
 
     if !response.status().is_success() {
-        return Err(format!("RemoteOK API request failed with status: {}", response.status()).into());
+        return Err(ServiceError::from_status("RemoteOK API", response.status()));
     }
 
     let jobs_data: Vec<serde_json::Value> = response.json().await?;
+    // The first element is RemoteOK's legal notice, not a job.
+    Ok(jobs_data.into_iter().skip(1).collect())
+}
+
+fn filter_remoteok_jobs(
+    feed: &[serde_json::Value],
+    query: &str,
+    limit: u32,
+    job_type: Option<&str>,
+    is_trending: bool,
+) -> Vec<Job> {
     let query_lower = query.to_lowercase();
     let query_parts: Vec<&str> = query_lower.split_whitespace().collect();
 
     // Define common filler words to exclude from matching
-    let filler_words = vec!["jobs", "trending", "remote", "work", "career", "opportunity"];
+    let filler_words = ["jobs", "trending", "remote", "work", "career", "opportunity"];
     let meaningful_parts: Vec<&str> = query_parts
         .iter()
         .copied()
         .filter(|&part| !filler_words.contains(&part) && part.len() > 2) // Exclude short words
         .collect();
 
+    let salary_regex = Regex::new(r"\$(\d+(?:,\d+)*(?:\.\d+)?)\s*(?:-|\s*to\s*)\s*\$?(\d+(?:,\d+)*(?:\.\d+)?)\s*(?:a year)?").unwrap();
+
     let mut jobs = Vec::new();
-    for job in jobs_data.iter().skip(1) {
+    for job in feed {
         let position = job
             .get("position")
             .and_then(|p| p.as_str())
@@ -202,7 +275,7 @@ async fn fetch_remoteok_jobs(
         let determined_job_type = determine_job_type(job);
         if let Some(jt) = job_type {
             let jt_lower = jt.to_lowercase();
-            let type_matches = determined_job_type.as_ref().map_or(false, |t| {
+            let type_matches = determined_job_type.as_ref().is_some_and(|t| {
                 t.to_lowercase().contains(&jt_lower)
                     || (jt_lower == "full-time" && t.to_lowercase().contains("full"))
                     || (jt_lower == "part-time" && t.to_lowercase().contains("part"))
@@ -263,11 +336,10 @@ async fn fetch_remoteok_jobs(
             .to_string();
 
         // If salary field is empty, extract from description
-        if salary_text.is_empty() {
-            let salary_regex = Regex::new(r"\$(\d+(?:,\d+)*(?:\.\d+)?)\s*(?:-|\s*to\s*)\s*\$?(\d+(?:,\d+)*(?:\.\d+)?)\s*(?:a year)?").unwrap();
-            if let Some(caps) = salary_regex.captures(&description) {
-                salary_text = format!("${} - ${}", caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str());
-            }
+        if salary_text.is_empty()
+            && let Some(caps) = salary_regex.captures(&description)
+        {
+            salary_text = format!("${} - ${}", caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str());
         }
 
         let (salary_min, salary_max) = parse_salary(&salary_text);
@@ -303,29 +375,16 @@ async fn fetch_remoteok_jobs(
         }
     }
 
-    Ok(jobs)
+    jobs
 }
 
-async fn fetch_remoteok_jobs_with_location(
+fn filter_remoteok_jobs_with_location(
+    feed: &[serde_json::Value],
     query: &str,
     limit: u32,
     location: &str,
     job_type: Option<&str>,
-) -> Result<Vec<Job>, Box<dyn Error>> {
-    let api_url = "https://remoteok.io/api";
-
-    let client = reqwest::Client::builder()
-        .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
-        .timeout(Duration::from_secs(10))
-        .build()?;
-
-    let response = client.get(api_url).send().await?;
-
-    if !response.status().is_success() {
-        return Err(format!("RemoteOK API request failed with status: {}", response.status()).into());
-    }
-
-    let jobs_data: Vec<serde_json::Value> = response.json().await?;
+) -> Vec<Job> {
     let query_lower = query.to_lowercase();
     let query_parts: Vec<&str> = query_lower.split_whitespace().collect();
     let location_lower = location.to_lowercase();
@@ -333,7 +392,7 @@ async fn fetch_remoteok_jobs_with_location(
     let city = location_parts.first().copied().unwrap_or(&location_lower);
 
     let mut jobs = Vec::new();
-    for job in jobs_data.iter().skip(1) {
+    for job in feed {
         let position = job
             .get("position")
             .and_then(|p| p.as_str())
@@ -366,7 +425,7 @@ async fn fetch_remoteok_jobs_with_location(
         let determined_job_type = determine_job_type(job);
         if let Some(jt) = job_type {
             let jt_lower = jt.to_lowercase();
-            let type_matches = determined_job_type.as_ref().map_or(false, |t| {
+            let type_matches = determined_job_type.as_ref().is_some_and(|t| {
                 t.to_lowercase().contains(&jt_lower) ||
                 (jt_lower == "full-time" && t.to_lowercase().contains("full")) ||
                 (jt_lower == "part-time" && t.to_lowercase().contains("part")) ||
@@ -421,7 +480,7 @@ async fn fetch_remoteok_jobs_with_location(
         let (salary_min, salary_max) = job
             .get("salary")
             .and_then(|s| s.as_str())
-            .map(|s| parse_salary(s))
+            .map(parse_salary)
             .unwrap_or((None, None));
 
         let logo = job
@@ -455,7 +514,7 @@ async fn fetch_remoteok_jobs_with_location(
         }
     }
 
-    Ok(jobs)
+    jobs
 }
 
 // Helper function to determine job type from RemoteOK data
@@ -528,7 +587,7 @@ fn extract_job_type(text: &str) -> Option<String> {
     // Check for Internship, but exclude cases where it's negated
     let has_intern = re_intern.find_iter(&text).any(|mat| {
         let start = mat.start();
-        let prefix_start = if start >= 20 { start - 20 } else { 0 };
+        let prefix_start = start.saturating_sub(20);
         let prefix = &text[prefix_start..start];
         !prefix.contains("not hiring associate/")
     });
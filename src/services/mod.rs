@@ -1,3 +1,10 @@
 pub mod cache;
+pub mod channel_service;
+pub mod error;
 pub mod job_service;
+pub mod playlist_service;
+pub mod refresh_service;
+pub mod transcript_service;
+pub mod video_detail_service;
+pub mod warmup_service;
 pub mod youtube_service;
@@ -0,0 +1,293 @@
+use chrono::Utc;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use crate::services::cache::{self, Cached};
+use crate::services::error::ServiceError;
+use crate::services::video_detail_service::embedded_json;
+use crate::services::youtube_service::{
+    approximate_publish_date, channel_id, determine_difficulty, fetch_continuation, parse_duration, parse_view_count,
+    text, thumbnail_url, youtube_client, Video,
+};
+
+lazy_static! {
+    static ref PLAYLIST_ID: Regex = Regex::new(r"^[A-Za-z0-9_-]{2,64}$").unwrap();
+}
+
+// Upper bound on upstream requests per playlist; YouTube lists 100 videos a
+// page.
+const MAX_PLAYLIST_PAGES: usize = 20;
+
+/// A playlist and its videos, in order.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Playlist {
+    pub playlist_id: String,
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub channel_name: Option<String>,
+    pub channel_id: Option<String>,
+    /// Video count YouTube shows, which includes videos left out of `videos`.
+    pub video_count: Option<u64>,
+    /// Sum of the lengths of `videos`.
+    pub total_duration_secs: u64,
+    /// Private, deleted and otherwise unplayable videos are left out.
+    pub videos: Vec<Video>,
+}
+
+impl cache::IsEmpty for Playlist {}
+
+/// A playlist expanded into its videos, from the cache or YouTube.
+pub async fn handle_playlist(playlist_id: &str) -> Result<Cached<Playlist>, ServiceError> {
+    validate_playlist_id(playlist_id)?;
+    let owned_id = playlist_id.to_string();
+    cache::get_or_fetch(&cache_key(playlist_id), &playlist_id, move || async move {
+        fetch_playlist(&owned_id).await
+    })
+    .await
+}
+
+/// Re-expands a playlist from the playlist ID stored with its cache entry and
+/// returns how many videos it has.
+pub async fn refresh_playlist(origin: Value) -> Result<usize, ServiceError> {
+    let playlist_id: String = serde_json::from_value(origin)?;
+    validate_playlist_id(&playlist_id)?;
+    let key = cache_key(&playlist_id);
+    let origin = playlist_id.clone();
+    let playlist = cache::refresh(&key, &origin, move || async move { fetch_playlist(&playlist_id).await }).await?;
+    Ok(playlist.videos.len())
+}
+
+fn validate_playlist_id(playlist_id: &str) -> Result<(), ServiceError> {
+    if PLAYLIST_ID.is_match(playlist_id) {
+        Ok(())
+    } else {
+        Err(ServiceError::InvalidParams(format!("invalid playlist ID: {}", playlist_id)))
+    }
+}
+
+fn cache_key(playlist_id: &str) -> String {
+    format!("playlist_{}", playlist_id)
+}
+
+async fn fetch_playlist(playlist_id: &str) -> Result<Playlist, ServiceError> {
+    let url = format!("https://www.youtube.com/playlist?list={}", playlist_id);
+    log::info!("Fetching YouTube playlist: {}", url);
+
+    let client = youtube_client()?;
+    let response = client
+        .get(&url)
+        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+        .header("Accept-Language", "en-US,en;q=0.5")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(ServiceError::from_status("YouTube", response.status()));
+    }
+    let html = response.text().await?;
+    let data = embedded_json(&html, "ytInitialData")
+        .ok_or_else(|| ServiceError::Parse("Could not find ytInitialData".to_string()))?;
+
+    let Some(items) = playlist_items(&data) else {
+        // Missing and private playlists render an alert instead of a list.
+        if let Some(alert) = alert_text(&data) {
+            return Err(ServiceError::NotFound(format!("{}: {}", playlist_id, alert)));
+        }
+        return Err(ServiceError::Parse("Could not find playlist contents in JSON".to_string()));
+    };
+    let (mut videos, mut continuation) = parse_playlist_items(items);
+
+    for _ in 1..MAX_PLAYLIST_PAGES {
+        let Some(token) = continuation.take() else {
+            break;
+        };
+        log::info!("Fetching YouTube playlist continuation for {}", playlist_id);
+        let response = fetch_continuation(&client, "browse", &token).await?;
+        if !response.status().is_success() {
+            return Err(ServiceError::from_status("YouTube", response.status()));
+        }
+        let json_data: Value = response.json().await?;
+        let items = json_data
+            .get("onResponseReceivedActions")
+            .and_then(|a| a.as_array())
+            .and_then(|actions| {
+                actions.iter().find_map(|action| {
+                    action
+                        .get("appendContinuationItemsAction")
+                        .and_then(|a| a.get("continuationItems"))
+                        .and_then(|items| items.as_array())
+                })
+            })
+            .ok_or_else(|| ServiceError::Parse("Could not find continuation items in JSON".to_string()))?;
+        let (more, next) = parse_playlist_items(items);
+        videos.extend(more);
+        continuation = next;
+    }
+    if continuation.is_some() {
+        log::warn!("Playlist {} has more than {} pages; the rest is left out", playlist_id, MAX_PLAYLIST_PAGES);
+    }
+
+    let metadata = data.get("metadata").and_then(|m| m.get("playlistMetadataRenderer"));
+    let header = data.get("header").and_then(|h| h.get("playlistHeaderRenderer"));
+    let sidebar_item = |renderer: &str| {
+        data.get("sidebar")
+            .and_then(|s| s.get("playlistSidebarRenderer"))
+            .and_then(|s| s.get("items"))
+            .and_then(|items| items.as_array())
+            .and_then(|items| items.iter().find_map(|item| item.get(renderer)))
+    };
+    let owner = header.and_then(|h| h.get("ownerText")).or_else(|| {
+        sidebar_item("playlistSidebarSecondaryInfoRenderer")
+            .and_then(|s| s.get("videoOwner"))
+            .and_then(|o| o.get("videoOwnerRenderer"))
+            .and_then(|o| o.get("title"))
+    });
+    let video_count = text(header.and_then(|h| h.get("numVideosText")))
+        .or_else(|| {
+            text(
+                sidebar_item("playlistSidebarPrimaryInfoRenderer")
+                    .and_then(|s| s.get("stats"))
+                    .and_then(|s| s.get(0)),
+            )
+        })
+        .and_then(|count| parse_view_count(&count));
+
+    Ok(Playlist {
+        playlist_id: playlist_id.to_string(),
+        url,
+        title: metadata
+            .and_then(|m| m.get("title"))
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        description: metadata
+            .and_then(|m| m.get("description"))
+            .and_then(|d| d.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        channel_name: text(owner),
+        channel_id: channel_id(owner),
+        video_count,
+        total_duration_secs: videos.iter().filter_map(|video| video.duration_secs).sum(),
+        videos,
+    })
+}
+
+fn playlist_items(data: &Value) -> Option<&Vec<Value>> {
+    data.get("contents")?
+        .get("twoColumnBrowseResultsRenderer")?
+        .get("tabs")?
+        .as_array()?
+        .iter()
+        .filter_map(|tab| {
+            tab.get("tabRenderer")?
+                .get("content")?
+                .get("sectionListRenderer")?
+                .get("contents")?
+                .as_array()
+        })
+        .flatten()
+        .filter_map(|section| section.get("itemSectionRenderer")?.get("contents")?.as_array())
+        .flatten()
+        .find_map(|item| item.get("playlistVideoListRenderer")?.get("contents")?.as_array())
+}
+
+fn alert_text(data: &Value) -> Option<String> {
+    data.get("alerts")?.as_array()?.iter().find_map(|alert| {
+        let renderer = alert
+            .get("alertRenderer")
+            .or_else(|| alert.get("alertWithButtonRenderer"))?;
+        text(renderer.get("text"))
+    })
+}
+
+/// Reads the videos and the next continuation token out of a list of playlist
+/// items; the HTML page and continuation responses share this shape.
+fn parse_playlist_items(items: &[Value]) -> (Vec<Video>, Option<String>) {
+    let mut videos = Vec::new();
+    let mut continuation = None;
+    for item in items {
+        if let Some(renderer) = item.get("playlistVideoRenderer") {
+            videos.extend(parse_playlist_video_renderer(renderer));
+        } else if let Some(endpoint) = item
+            .get("continuationItemRenderer")
+            .and_then(|c| c.get("continuationEndpoint"))
+        {
+            continuation = continuation_token(endpoint);
+        }
+    }
+    (videos, continuation)
+}
+
+/// The token is either on the endpoint itself or in one of the commands it
+/// runs.
+fn continuation_token(endpoint: &Value) -> Option<String> {
+    let token = |command: &Value| {
+        command
+            .get("continuationCommand")
+            .and_then(|c| c.get("token"))
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+    };
+    token(endpoint).or_else(|| {
+        endpoint
+            .get("commandExecutorCommand")?
+            .get("commands")?
+            .as_array()?
+            .iter()
+            .find_map(token)
+    })
+}
+
+fn parse_playlist_video_renderer(renderer: &Value) -> Option<Video> {
+    if renderer.get("isPlayable").and_then(|p| p.as_bool()) == Some(false) {
+        return None;
+    }
+    let video_id = renderer.get("videoId").and_then(|v| v.as_str()).filter(|id| !id.is_empty())?;
+    let title = text(renderer.get("title")).filter(|title| !title.is_empty())?;
+    let owner = renderer.get("shortBylineText");
+    let duration_secs = renderer
+        .get("lengthSeconds")
+        .and_then(|l| l.as_str())
+        .and_then(|secs| secs.parse().ok())
+        .or_else(|| text(renderer.get("lengthText")).and_then(|length| parse_duration(&length)));
+    // "1.2M views • 3 years ago"; the view count is abbreviated, so only the
+    // publish time is used.
+    let published_text = renderer
+        .get("videoInfo")
+        .and_then(|i| i.get("runs"))
+        .and_then(|r| r.as_array())
+        .and_then(|runs| {
+            runs.iter()
+                .filter_map(|run| run.get("text").and_then(|t| t.as_str()))
+                .find(|run| run.contains(" ago"))
+        })
+        .map(|published| published.to_string());
+
+    Some(Video {
+        url: format!("https://www.youtube.com/watch?v={}", video_id),
+        video_id: video_id.to_string(),
+        r#type: "video".to_string(),
+        free: true,
+        image: thumbnail_url(renderer.get("thumbnail")),
+        source: "YouTube".to_string(),
+        difficulty: determine_difficulty(&title),
+        title,
+        description: String::new(),
+        channel_name: text(owner),
+        channel_id: channel_id(owner),
+        duration_secs,
+        view_count: None,
+        published_date: published_text
+            .as_deref()
+            .and_then(|published| approximate_publish_date(published, Utc::now())),
+        published_text,
+        is_short: false,
+        is_live: false,
+        is_upcoming: renderer.get("upcomingEventData").is_some(),
+        playlist_id: None,
+        video_count: None,
+    })
+}
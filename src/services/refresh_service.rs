@@ -0,0 +1,39 @@
+use std::time::Instant;
+use serde::Serialize;
+use utoipa::ToSchema;
+use crate::services::error::ServiceError;
+use crate::services::{cache, channel_service, job_service, playlist_service, transcript_service, video_detail_service, youtube_service};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RefreshResult {
+    pub cache_key: String,
+    /// Number of items the fresh fetch returned.
+    pub items: usize,
+    pub duration_ms: u64,
+}
+
+/// Re-fetches the data behind `cache_key` using the request parameters stored
+/// with the entry. Returns `Ok(None)` when the key is not cached or was stored
+/// without parameters, so there is nothing to re-run.
+pub async fn refresh_cache_key(cache_key: &str) -> Result<Option<RefreshResult>, ServiceError> {
+    let Some(origin) = cache::origin(cache_key).await else {
+        return Ok(None);
+    };
+
+    let started = Instant::now();
+    let items = match cache_key.split('_').next().unwrap_or("") {
+        "videos" => youtube_service::refresh_video_search(origin).await?,
+        "watch" => video_detail_service::refresh_video_details(origin).await?,
+        "transcript" => transcript_service::refresh_transcript(origin).await?,
+        "playlist" => playlist_service::refresh_playlist(origin).await?,
+        "channel" => channel_service::refresh_channel_feed(origin).await?,
+        "feed" => job_service::refresh_remoteok_feed().await?,
+        _ => return Ok(None),
+    };
+
+    Ok(Some(RefreshResult {
+        cache_key: cache_key.to_string(),
+        items,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }))
+}
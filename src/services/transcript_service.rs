@@ -0,0 +1,370 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use crate::services::cache::{self, Cached};
+use crate::services::error::ServiceError;
+use crate::services::video_detail_service::{fetch_watch_page, validate_video_id};
+use crate::services::youtube_service::{text, youtube_client};
+
+/// A caption track listed in a video's player response.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CaptionTrack {
+    /// Language code, e.g. `en` or `pt-BR`.
+    pub language_code: String,
+    /// Display name, e.g. "English (auto-generated)".
+    pub name: String,
+    /// Generated by YouTube's speech recognition rather than uploaded.
+    pub is_auto_generated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TranscriptSegment {
+    pub start_secs: f64,
+    pub duration_secs: f64,
+    pub text: String,
+}
+
+/// The captions of one track of a video.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Transcript {
+    #[serde(rename = "videoId")]
+    pub video_id: String,
+    /// The track the segments were read from.
+    pub track: CaptionTrack,
+    /// Every track the video has; pass one's `language_code` as `lang` to
+    /// read it instead.
+    pub available_tracks: Vec<CaptionTrack>,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl cache::IsEmpty for Transcript {}
+
+impl Transcript {
+    /// The spoken text alone, one segment per line.
+    pub fn plain_text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Transcript of a video, from the cache or YouTube. Without `lang` the first
+/// uploaded track is used; `auto_generated` prefers YouTube's speech
+/// recognition track over uploaded ones in the same language.
+pub async fn handle_transcript(
+    video_id: &str,
+    lang: Option<&str>,
+    auto_generated: bool,
+) -> Result<Cached<Transcript>, ServiceError> {
+    validate_video_id(video_id)?;
+    if let Some(lang) = lang
+        && !is_language_code(lang)
+    {
+        return Err(ServiceError::InvalidParams(format!("invalid language code: {}", lang)));
+    }
+    let request = TranscriptRequest {
+        video_id: video_id.to_string(),
+        lang: lang.map(|lang| lang.to_lowercase()),
+        auto_generated,
+    };
+    let cache_key = request.cache_key();
+    let origin = request.clone();
+    cache::get_or_fetch(&cache_key, &origin, move || async move { request.fetch().await }).await
+}
+
+/// Re-downloads a transcript from the request stored with its cache entry and
+/// returns how many segments it has.
+pub async fn refresh_transcript(origin: Value) -> Result<usize, ServiceError> {
+    let request: TranscriptRequest = serde_json::from_value(origin)?;
+    validate_video_id(&request.video_id)?;
+    let cache_key = request.cache_key();
+    let origin = request.clone();
+    let transcript = cache::refresh(&cache_key, &origin, move || async move { request.fetch().await }).await?;
+    Ok(transcript.segments.len())
+}
+
+fn is_language_code(lang: &str) -> bool {
+    !lang.is_empty() && lang.len() <= 20 && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Transcript parameters, stored as the cache entry's origin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TranscriptRequest {
+    video_id: String,
+    lang: Option<String>,
+    auto_generated: bool,
+}
+
+impl TranscriptRequest {
+    fn cache_key(&self) -> String {
+        format!(
+            "transcript_{}_{}_{}",
+            self.video_id,
+            self.lang.as_deref().unwrap_or(""),
+            if self.auto_generated { "auto" } else { "" }
+        )
+    }
+
+    async fn fetch(&self) -> Result<Transcript, ServiceError> {
+        let page = fetch_watch_page(&self.video_id).await?;
+        let tracks = caption_tracks(&page.player);
+        if tracks.is_empty() {
+            return Err(ServiceError::NotFound(format!("{} has no captions", self.video_id)));
+        }
+        let source = select_track(&tracks, self.lang.as_deref(), self.auto_generated).ok_or_else(|| {
+            let available: Vec<&str> = tracks.iter().map(|t| t.track.language_code.as_str()).collect();
+            ServiceError::NotFound(format!(
+                "{} has no `{}` captions, available: {}",
+                self.video_id,
+                self.lang.as_deref().unwrap_or_default(),
+                available.join(", ")
+            ))
+        })?;
+
+        log::info!("Fetching {} captions for {}", source.track.language_code, self.video_id);
+        let response = youtube_client()?.get(&source.base_url).send().await?;
+        if !response.status().is_success() {
+            return Err(ServiceError::from_status("YouTube", response.status()));
+        }
+        let xml = response.text().await?;
+        if xml.trim().is_empty() {
+            return Err(ServiceError::Upstream(format!(
+                "YouTube returned empty {} captions for {}",
+                source.track.language_code, self.video_id
+            )));
+        }
+
+        Ok(Transcript {
+            video_id: self.video_id.clone(),
+            track: source.track.clone(),
+            available_tracks: tracks.iter().map(|t| t.track.clone()).collect(),
+            segments: parse_timed_text(&xml)?,
+        })
+    }
+}
+
+/// A caption track and the timed-text URL its captions are downloaded from.
+struct TrackSource {
+    track: CaptionTrack,
+    base_url: String,
+}
+
+fn caption_tracks(player: &Value) -> Vec<TrackSource> {
+    let Some(tracks) = player
+        .get("captions")
+        .and_then(|c| c.get("playerCaptionsTracklistRenderer"))
+        .and_then(|r| r.get("captionTracks"))
+        .and_then(|t| t.as_array())
+    else {
+        return Vec::new();
+    };
+    tracks
+        .iter()
+        .filter_map(|track| {
+            let language_code = track.get("languageCode")?.as_str()?.to_string();
+            Some(TrackSource {
+                base_url: track.get("baseUrl")?.as_str()?.to_string(),
+                track: CaptionTrack {
+                    name: text(track.get("name")).unwrap_or_else(|| language_code.clone()),
+                    language_code,
+                    is_auto_generated: track.get("kind").and_then(|k| k.as_str()) == Some("asr"),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Picks the track for `lang`, matching either the full code or just the
+/// language (`en` matches `en-GB`), and preferring uploaded or auto-generated
+/// captions as asked. Falls back to the other kind when that is all there is.
+fn select_track<'a>(tracks: &'a [TrackSource], lang: Option<&str>, auto_generated: bool) -> Option<&'a TrackSource> {
+    let matching: Vec<&TrackSource> = tracks
+        .iter()
+        .filter(|source| match lang {
+            Some(lang) => {
+                let code = &source.track.language_code;
+                code.eq_ignore_ascii_case(lang)
+                    || code.split('-').next().is_some_and(|language| language.eq_ignore_ascii_case(lang))
+            }
+            None => true,
+        })
+        .collect();
+    matching
+        .iter()
+        .find(|source| source.track.is_auto_generated == auto_generated)
+        .or(matching.first())
+        .copied()
+}
+
+/// Parses YouTube's timed-text XML: the default format of `<text start dur>`
+/// elements in seconds, or format 3 (`fmt=srv3`) with `<p t d>` elements in
+/// milliseconds.
+fn parse_timed_text(xml: &str) -> Result<Vec<TranscriptSegment>, ServiceError> {
+    let mut reader = Reader::from_str(xml);
+    let mut segments = Vec::new();
+    let mut current: Option<(f64, f64, String)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) => match element.name().as_ref() {
+                b"text" => {
+                    current = Some((
+                        number_attribute(&element, "start").unwrap_or(0.0),
+                        number_attribute(&element, "dur").unwrap_or(0.0),
+                        String::new(),
+                    ));
+                }
+                b"p" => {
+                    current = Some((
+                        number_attribute(&element, "t").unwrap_or(0.0) / 1000.0,
+                        number_attribute(&element, "d").unwrap_or(0.0) / 1000.0,
+                        String::new(),
+                    ));
+                }
+                _ => {}
+            },
+            Ok(Event::Text(content)) => {
+                if let Some((_, _, text)) = &mut current {
+                    let content = content
+                        .unescape()
+                        .map_err(|e| ServiceError::Parse(format!("Invalid timed text: {}", e)))?;
+                    text.push_str(&content);
+                }
+            }
+            Ok(Event::End(element)) if matches!(element.name().as_ref(), b"text" | b"p") => {
+                if let Some((start_secs, duration_secs, text)) = current.take() {
+                    let text = clean_caption(&text);
+                    if !text.is_empty() {
+                        segments.push(TranscriptSegment {
+                            start_secs,
+                            duration_secs,
+                            text,
+                        });
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(ServiceError::Parse(format!("Invalid timed text: {}", e))),
+        }
+    }
+    Ok(segments)
+}
+
+fn number_attribute(element: &BytesStart, name: &str) -> Option<f64> {
+    let attribute = element.try_get_attribute(name).ok()??;
+    std::str::from_utf8(&attribute.value).ok()?.parse().ok()
+}
+
+/// Caption text is escaped twice (`&amp;#39;`), so entities left after XML
+/// unescaping are decoded again; line breaks within a caption become spaces.
+fn clean_caption(text: &str) -> String {
+    let text = quick_xml::escape::unescape(text)
+        .map(|t| t.into_owned())
+        .unwrap_or_else(|_| text.to_string());
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(xml: &str) -> Vec<(f64, f64, String)> {
+        parse_timed_text(xml)
+            .unwrap()
+            .into_iter()
+            .map(|segment| (segment.start_secs, segment.duration_secs, segment.text))
+            .collect()
+    }
+
+    fn source(language_code: &str, is_auto_generated: bool) -> TrackSource {
+        TrackSource {
+            track: CaptionTrack {
+                language_code: language_code.to_string(),
+                name: language_code.to_string(),
+                is_auto_generated,
+            },
+            base_url: format!("https://example.com/{}/{}", language_code, is_auto_generated),
+        }
+    }
+
+    fn selected(tracks: &[TrackSource], lang: Option<&str>, auto_generated: bool) -> Option<(String, bool)> {
+        select_track(tracks, lang, auto_generated)
+            .map(|source| (source.track.language_code.clone(), source.track.is_auto_generated))
+    }
+
+    #[test]
+    fn parses_the_default_format_in_seconds() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?><transcript><text start="0.5" dur="2.25">Hello
+world</text><text start="2.75" dur="1">  </text><text start="3" dur="1.5">Bye</text></transcript>"#;
+        assert_eq!(
+            segments(xml),
+            vec![(0.5, 2.25, "Hello world".to_string()), (3.0, 1.5, "Bye".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_srv3_in_milliseconds_with_nested_words() {
+        let xml = r#"<timedtext format="3"><body><p t="1200" d="3400"><s>Hello</s><s t="500"> there</s></p><p t="4600" d="800">again</p></body></timedtext>"#;
+        assert_eq!(
+            segments(xml),
+            vec![(1.2, 3.4, "Hello there".to_string()), (4.6, 0.8, "again".to_string())]
+        );
+    }
+
+    #[test]
+    fn decodes_doubly_escaped_entities() {
+        let xml = r#"<transcript><text start="0" dur="1">it&amp;#39;s &amp;quot;fine&amp;quot;</text></transcript>"#;
+        assert_eq!(segments(xml)[0].2, r#"it's "fine""#);
+    }
+
+    #[test]
+    fn keeps_a_singly_escaped_ampersand() {
+        let xml = r#"<transcript><text start="0" dur="1">Tom &amp; Jerry</text></transcript>"#;
+        assert_eq!(segments(xml)[0].2, "Tom & Jerry");
+    }
+
+    #[test]
+    fn clean_caption_joins_lines() {
+        assert_eq!(clean_caption("  one\ntwo   three "), "one two three");
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(parse_timed_text("<transcript><text start=\"0\">open</p>").is_err());
+    }
+
+    #[test]
+    fn selects_by_full_code_or_language() {
+        let tracks = [source("de", false), source("en-GB", false), source("en", true)];
+        assert_eq!(selected(&tracks, Some("en-gb"), false), Some(("en-GB".to_string(), false)));
+        assert_eq!(selected(&tracks, Some("en"), false), Some(("en-GB".to_string(), false)));
+        assert_eq!(selected(&tracks, Some("fr"), false), None);
+    }
+
+    #[test]
+    fn prefers_the_requested_kind_of_track() {
+        let tracks = [source("en", true), source("en", false)];
+        assert_eq!(selected(&tracks, Some("en"), false), Some(("en".to_string(), false)));
+        assert_eq!(selected(&tracks, Some("en"), true), Some(("en".to_string(), true)));
+    }
+
+    #[test]
+    fn falls_back_to_the_other_kind_of_track() {
+        let uploaded_only = [source("en", false)];
+        assert_eq!(selected(&uploaded_only, Some("en"), true), Some(("en".to_string(), false)));
+        let auto_only = [source("en", true)];
+        assert_eq!(selected(&auto_only, None, false), Some(("en".to_string(), true)));
+    }
+
+    #[test]
+    fn without_a_language_takes_the_first_uploaded_track() {
+        let tracks = [source("es", true), source("pt-BR", false), source("en", false)];
+        assert_eq!(selected(&tracks, None, false), Some(("pt-BR".to_string(), false)));
+        assert_eq!(selected(&[], None, false), None);
+    }
+}
@@ -0,0 +1,379 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use crate::services::cache::{self, Cached};
+use crate::services::error::ServiceError;
+use crate::services::youtube_service::{parse_duration, parse_view_count, text, youtube_client};
+
+lazy_static! {
+    static ref VIDEO_ID: Regex = Regex::new(r"^[A-Za-z0-9_-]{11}$").unwrap();
+    static ref LIKE_COUNT: Regex = Regex::new(r"along with ([\d,]+) other (?:people|person)").unwrap();
+    // "0:00 Intro", "(1:02:03) - Setup", "• 12:34 | Deploy"
+    static ref LEADING_TIMESTAMP: Regex =
+        Regex::new(r"^[^\w(]*\(?(?P<time>(?:\d{1,2}:)?\d{1,2}:\d{2})\)?\s*[-–—:|.]?\s*(?P<title>.+)$").unwrap();
+    // "Intro 0:00", "Setup - (1:02:03)"
+    static ref TRAILING_TIMESTAMP: Regex =
+        Regex::new(r"^[^\w(]*(?P<title>.+?)\s*[-–—:|]?\s*\(?(?P<time>(?:\d{1,2}:)?\d{1,2}:\d{2})\)?$").unwrap();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Chapter {
+    pub title: String,
+    #[serde(rename = "start_seconds")]
+    pub start_secs: u64,
+    /// Watch page link that starts playback at the chapter.
+    pub url: String,
+}
+
+impl Chapter {
+    fn new(video_id: &str, title: String, start_secs: u64) -> Self {
+        Chapter {
+            url: format!("https://www.youtube.com/watch?v={}&t={}s", video_id, start_secs),
+            title,
+            start_secs,
+        }
+    }
+}
+
+/// Everything the watch page tells us about a single video.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VideoDetails {
+    #[serde(rename = "videoId")]
+    pub video_id: String,
+    pub url: String,
+    pub title: String,
+    /// Full description.
+    pub description: String,
+    pub image: String,
+    pub channel_name: Option<String>,
+    pub channel_id: Option<String>,
+    pub duration_secs: Option<u64>,
+    /// As YouTube reports it: a date, or a date and time with offset.
+    pub publish_date: Option<String>,
+    pub view_count: Option<u64>,
+    /// Not shown for every video, and read from the page's like button, so
+    /// absent when YouTube changes its layout.
+    pub like_count: Option<u64>,
+    pub keywords: Vec<String>,
+    pub category: Option<String>,
+    /// Streamed live, now or in the past.
+    pub is_live_content: bool,
+    pub chapters: Vec<Chapter>,
+}
+
+impl cache::IsEmpty for VideoDetails {}
+
+/// Details for one video, from the cache or the watch page.
+pub async fn handle_video_details(video_id: &str) -> Result<Cached<VideoDetails>, ServiceError> {
+    validate_video_id(video_id)?;
+    let owned_id = video_id.to_string();
+    cache::get_or_fetch(&cache_key(video_id), &video_id, move || async move {
+        fetch_video_details(&owned_id).await
+    })
+    .await
+}
+
+/// Re-scrapes a video's details from the video ID stored with its cache entry.
+pub async fn refresh_video_details(origin: Value) -> Result<usize, ServiceError> {
+    let video_id: String = serde_json::from_value(origin)?;
+    validate_video_id(&video_id)?;
+    let key = cache_key(&video_id);
+    let origin = video_id.clone();
+    cache::refresh(&key, &origin, move || async move { fetch_video_details(&video_id).await }).await?;
+    Ok(1)
+}
+
+pub(crate) fn validate_video_id(video_id: &str) -> Result<(), ServiceError> {
+    if VIDEO_ID.is_match(video_id) {
+        Ok(())
+    } else {
+        Err(ServiceError::InvalidParams(format!("invalid video ID: {}", video_id)))
+    }
+}
+
+fn cache_key(video_id: &str) -> String {
+    format!("watch_{}", video_id)
+}
+
+/// The data embedded in a video's watch page.
+pub(crate) struct WatchPage {
+    html: String,
+    /// `ytInitialPlayerResponse`: video metadata, captions, streaming data.
+    pub(crate) player: Value,
+    /// `ytInitialData`: the rendered page around the player.
+    initial_data: Value,
+}
+
+pub(crate) async fn fetch_watch_page(video_id: &str) -> Result<WatchPage, ServiceError> {
+    let url = format!("https://www.youtube.com/watch?v={}", video_id);
+    log::info!("Fetching YouTube watch page: {}", url);
+
+    let response = youtube_client()?
+        .get(&url)
+        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+        .header("Accept-Language", "en-US,en;q=0.5")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(ServiceError::from_status("YouTube", response.status()));
+    }
+    let html = response.text().await?;
+
+    let player = embedded_json(&html, "ytInitialPlayerResponse")
+        .ok_or_else(|| ServiceError::Parse("Could not find ytInitialPlayerResponse".to_string()))?;
+    let status = player
+        .get("playabilityStatus")
+        .and_then(|p| p.get("status"))
+        .and_then(|s| s.as_str())
+        .unwrap_or("OK");
+    if player.get("videoDetails").is_none() && status != "OK" {
+        let reason = player
+            .get("playabilityStatus")
+            .and_then(|p| p.get("reason"))
+            .and_then(|r| r.as_str())
+            .unwrap_or("Video unavailable");
+        return Err(ServiceError::NotFound(format!("{}: {}", video_id, reason)));
+    }
+    let initial_data = embedded_json(&html, "ytInitialData").unwrap_or(Value::Null);
+
+    Ok(WatchPage {
+        html,
+        player,
+        initial_data,
+    })
+}
+
+/// Parses the JSON object assigned to `name` in one of the page's inline
+/// scripts. Only the object itself is read, whatever follows it.
+pub(crate) fn embedded_json(html: &str, name: &str) -> Option<Value> {
+    let assignment = html.find(&format!("{} = ", name))?;
+    let start = assignment + html[assignment..].find('{')?;
+    serde_json::Deserializer::from_str(&html[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+async fn fetch_video_details(video_id: &str) -> Result<VideoDetails, ServiceError> {
+    let page = fetch_watch_page(video_id).await?;
+    parse_video_details(video_id, &page)
+}
+
+fn parse_video_details(video_id: &str, page: &WatchPage) -> Result<VideoDetails, ServiceError> {
+    let details = page
+        .player
+        .get("videoDetails")
+        .ok_or_else(|| ServiceError::Parse("Could not find videoDetails".to_string()))?;
+    let microformat = page
+        .player
+        .get("microformat")
+        .and_then(|m| m.get("playerMicroformatRenderer"));
+    let str_field = |value: Option<&Value>, field: &str| {
+        value
+            .and_then(|v| v.get(field))
+            .and_then(|f| f.as_str())
+            .map(|f| f.to_string())
+    };
+
+    let image = details
+        .get("thumbnail")
+        .and_then(|t| t.get("thumbnails"))
+        .and_then(|t| t.as_array())
+        .and_then(|thumbnails| thumbnails.last())
+        .and_then(|t| t.get("url"))
+        .and_then(|u| u.as_str())
+        .map(|url| url.split('?').next().unwrap_or(url))
+        .unwrap_or("");
+    let keywords = details
+        .get("keywords")
+        .and_then(|k| k.as_array())
+        .map(|keywords| {
+            keywords
+                .iter()
+                .filter_map(|k| k.as_str())
+                .map(|k| k.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let description = str_field(Some(details), "shortDescription").unwrap_or_default();
+    let mut chapters = player_chapters(video_id, &page.initial_data);
+    if chapters.is_empty() {
+        chapters = description_chapters(video_id, &description);
+    }
+
+    Ok(VideoDetails {
+        video_id: video_id.to_string(),
+        url: format!("https://www.youtube.com/watch?v={}", video_id),
+        title: str_field(Some(details), "title").unwrap_or_default(),
+        description,
+        image: image.to_string(),
+        channel_name: str_field(Some(details), "author"),
+        channel_id: str_field(Some(details), "channelId"),
+        duration_secs: str_field(Some(details), "lengthSeconds").and_then(|secs| secs.parse().ok()),
+        publish_date: str_field(microformat, "publishDate"),
+        view_count: str_field(Some(details), "viewCount").and_then(|views| views.parse().ok()),
+        like_count: LIKE_COUNT
+            .captures(&page.html)
+            .and_then(|captures| parse_view_count(&captures[1])),
+        keywords,
+        category: str_field(microformat, "category"),
+        is_live_content: details
+            .get("isLiveContent")
+            .and_then(|l| l.as_bool())
+            .unwrap_or(false),
+        chapters,
+    })
+}
+
+/// Chapters from the player's progress bar markers, as shown on the page.
+/// YouTube may list both the uploader's and auto-generated chapters; the
+/// first set wins.
+fn player_chapters(video_id: &str, initial_data: &Value) -> Vec<Chapter> {
+    let markers = initial_data
+        .get("playerOverlays")
+        .and_then(|p| p.get("playerOverlayRenderer"))
+        .and_then(|p| p.get("decoratedPlayerBarRenderer"))
+        .and_then(|d| d.get("decoratedPlayerBarRenderer"))
+        .and_then(|d| d.get("playerBar"))
+        .and_then(|p| p.get("multiMarkersPlayerBarRenderer"))
+        .and_then(|m| m.get("markersMap"))
+        .and_then(|m| m.as_array());
+    let Some(markers) = markers else {
+        return Vec::new();
+    };
+
+    let Some(chapters) = markers
+        .iter()
+        .find_map(|marker| marker.get("value")?.get("chapters")?.as_array())
+    else {
+        return Vec::new();
+    };
+    chapters
+        .iter()
+        .filter_map(|chapter| {
+            let chapter = chapter.get("chapterRenderer")?;
+            Some(Chapter::new(
+                video_id,
+                text(chapter.get("title"))?,
+                chapter.get("timeRangeStartMillis")?.as_u64()? / 1000,
+            ))
+        })
+        .collect()
+}
+
+/// Chapters from timestamped lines in the description, for when the page has
+/// no player markers. Follows YouTube's own rules so a stray timestamp isn't
+/// taken for a chapter list: the chapters are the first block of consecutive
+/// timestamped lines starting at 0:00, all written the same way, at least
+/// three of them, and in order.
+fn description_chapters(video_id: &str, description: &str) -> Vec<Chapter> {
+    let mut lines = description.lines().map(str::trim);
+    let Some((form, first)) = lines.by_ref().find_map(|line| {
+        [&*LEADING_TIMESTAMP, &*TRAILING_TIMESTAMP]
+            .into_iter()
+            .find_map(|form| Some((form, timestamped_line(form, video_id, line)?)))
+            .filter(|(_, chapter)| chapter.start_secs == 0)
+    }) else {
+        return Vec::new();
+    };
+
+    let mut chapters = vec![first];
+    chapters.extend(lines.map_while(|line| timestamped_line(form, video_id, line)));
+
+    let ascending = chapters.windows(2).all(|pair| pair[0].start_secs < pair[1].start_secs);
+    if chapters.len() >= 3 && ascending {
+        chapters
+    } else {
+        Vec::new()
+    }
+}
+
+/// Reads one description line written in the given form, leading or
+/// trailing timestamp.
+fn timestamped_line(form: &Regex, video_id: &str, line: &str) -> Option<Chapter> {
+    let captures = form.captures(line)?;
+    Some(Chapter::new(
+        video_id,
+        captures.name("title")?.as_str().trim().to_string(),
+        parse_duration(captures.name("time")?.as_str())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapters(description: &str) -> Vec<(String, u64)> {
+        description_chapters("dQw4w9WgXcQ", description)
+            .into_iter()
+            .map(|chapter| (chapter.title, chapter.start_secs))
+            .collect()
+    }
+
+    fn expected(chapters: &[(&str, u64)]) -> Vec<(String, u64)> {
+        chapters.iter().map(|(title, secs)| (title.to_string(), *secs)).collect()
+    }
+
+    #[test]
+    fn reads_a_leading_timestamp_block() {
+        assert_eq!(
+            chapters("What we build today
+
+00:00 Intro
+01:30 Setup
+05:00 Build"),
+            expected(&[("Intro", 0), ("Setup", 90), ("Build", 300)])
+        );
+    }
+
+    #[test]
+    fn reads_a_trailing_timestamp_block() {
+        assert_eq!(
+            chapters("Intro - 0:00
+Setup (1:30)
+Build 1:05:00"),
+            expected(&[("Intro", 0), ("Setup", 90), ("Build", 3900)])
+        );
+    }
+
+    #[test]
+    fn ignores_timestamps_after_the_block() {
+        assert_eq!(
+            chapters("00:00 Intro
+01:30 Setup
+05:00 Build
+
+Recorded 2024 at 10:30"),
+            expected(&[("Intro", 0), ("Setup", 90), ("Build", 300)])
+        );
+        assert_eq!(
+            chapters("00:00 Intro
+01:30 Setup
+05:00 Build
+Live at 19:00"),
+            expected(&[("Intro", 0), ("Setup", 90), ("Build", 300)])
+        );
+    }
+
+    #[test]
+    fn stops_the_block_at_a_change_of_form() {
+        assert!(chapters("00:00 Intro
+Setup 01:30
+Build 05:00").is_empty());
+    }
+
+    #[test]
+    fn ignores_timestamps_not_starting_at_zero() {
+        assert!(chapters("Live at 19:00
+01:30 Setup
+05:00 Build").is_empty());
+        assert!(chapters("0:00 Intro
+0:30 Setup").is_empty());
+        assert!(chapters("0:00 Intro
+5:00 Setup
+1:30 Build").is_empty());
+    }
+}
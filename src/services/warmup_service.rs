@@ -0,0 +1,50 @@
+use std::time::Duration;
+use crate::services::youtube_service::VideoSorting;
+use crate::services::{job_service, youtube_service};
+use crate::settings::WarmupSettings;
+
+/// Spawns a background task that refreshes the configured popular searches
+/// right away and then every `interval_secs`, so they are cached before users
+/// ask for them. Does nothing when no searches are configured or the interval
+/// is 0.
+pub fn spawn_warmer(settings: &WarmupSettings) {
+    if settings.jobs.is_empty() && settings.videos.is_empty() {
+        return;
+    }
+    if settings.interval_secs == 0 {
+        log::info!("Cache warming disabled");
+        return;
+    }
+    let settings = settings.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(settings.interval_secs));
+        loop {
+            ticker.tick().await;
+            warm(&settings).await;
+        }
+    });
+}
+
+async fn warm(settings: &WarmupSettings) {
+    log::info!(
+        "Warming cache for {} job and {} video searches",
+        settings.jobs.len(),
+        settings.videos.len()
+    );
+
+    // Job searches are filtered from the shared RemoteOK feed on every
+    // request, so warming them means refreshing that feed once.
+    if !settings.jobs.is_empty() {
+        match job_service::refresh_remoteok_feed().await {
+            Ok(count) => log::info!("Warmed RemoteOK feed with {} jobs", count),
+            Err(e) => log::warn!("Failed to warm RemoteOK feed: {}", e),
+        }
+    }
+
+    for query in &settings.videos {
+        match youtube_service::refresh_youtube_scraper(query, settings.videos_limit, VideoSorting::Relevance).await {
+            Ok(count) => log::info!("Warmed video search '{}' with {} videos", query, count),
+            Err(e) => log::warn!("Failed to warm video search '{}': {}", query, e),
+        }
+    }
+}
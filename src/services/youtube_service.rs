@@ -1,9 +1,18 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
 use reqwest;
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::error::Error;
+use std::sync::Arc;
 use urlencoding::encode;
 use utoipa::ToSchema;
+use crate::services::cache::{self, Cached};
+use crate::services::error::{validate_search, ServiceError};
+
+lazy_static! {
+    static ref PUBLISHED_AGO: Regex = Regex::new(r"(\d+)\s+(second|minute|hour|day|week|month|year)s?\s+ago").unwrap();
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct Video {
@@ -16,30 +25,506 @@ pub struct Video {
     pub image: String,
     pub source: String,
     pub difficulty: String,
+    /// Description snippet shown under the search result.
     pub description: String,
+    pub channel_name: Option<String>,
+    pub channel_id: Option<String>,
+    pub duration_secs: Option<u64>,
+    pub view_count: Option<u64>,
+    /// Publish time as YouTube shows it: "3 years ago" in search results, an
+    /// RFC 3339 timestamp in channel feeds.
+    pub published_text: Option<String>,
+    /// Publish date (YYYY-MM-DD) derived from `published_text`, so only as
+    /// precise as that text.
+    pub published_date: Option<String>,
+    #[serde(default)]
+    pub is_short: bool,
+    /// Currently streaming live.
+    #[serde(default)]
+    pub is_live: bool,
+    /// A scheduled live stream or premiere that hasn't started yet.
+    #[serde(default)]
+    pub is_upcoming: bool,
+    /// Set when `type` is "playlist"; `videoId` is then the playlist's first
+    /// video and `url` the playlist page.
+    pub playlist_id: Option<String>,
+    /// Number of videos in the playlist.
+    pub video_count: Option<u64>,
+}
+
+/// Result order for a video search, mapped to YouTube's `sp` search filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum VideoSorting {
+    #[default]
+    Relevance,
+    UploadDate,
+    ViewCount,
+    Rating,
+}
+
+impl VideoSorting {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VideoSorting::Relevance => "relevance",
+            VideoSorting::UploadDate => "upload_date",
+            VideoSorting::ViewCount => "view_count",
+            VideoSorting::Rating => "rating",
+        }
+    }
+
+}
+
+impl TryFrom<String> for VideoSorting {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "relevance" => Ok(VideoSorting::Relevance),
+            "upload_date" => Ok(VideoSorting::UploadDate),
+            "view_count" => Ok(VideoSorting::ViewCount),
+            "rating" => Ok(VideoSorting::Rating),
+            _ => Err(format!(
+                "unknown sorting `{}`, expected one of relevance, upload_date, view_count, rating",
+                value
+            )),
+        }
+    }
+}
+
+/// What a search returns. Playlists (full courses, usually) come back as
+/// `Video` entries with `type` "playlist".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum SearchType {
+    #[default]
+    Video,
+    Playlist,
+}
+
+impl SearchType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchType::Video => "video",
+            SearchType::Playlist => "playlist",
+        }
+    }
+}
+
+impl TryFrom<String> for SearchType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "video" => Ok(SearchType::Video),
+            "playlist" => Ok(SearchType::Playlist),
+            _ => Err(format!("unknown type `{}`, expected video or playlist", value)),
+        }
+    }
+}
+
+/// Value of YouTube's `sp` query parameter: a base64 protobuf holding the sort
+/// order (field 1) and, for playlists, a result type filter (field 2).
+/// Relevance-sorted video searches need none.
+fn search_filter(sorting: VideoSorting, search_type: SearchType) -> Option<&'static str> {
+    match (search_type, sorting) {
+        (SearchType::Video, VideoSorting::Relevance) => None,
+        (SearchType::Video, VideoSorting::UploadDate) => Some("CAI%3D"),
+        (SearchType::Video, VideoSorting::ViewCount) => Some("CAM%3D"),
+        (SearchType::Video, VideoSorting::Rating) => Some("CAE%3D"),
+        (SearchType::Playlist, VideoSorting::Relevance) => Some("EgIQAw%3D%3D"),
+        (SearchType::Playlist, VideoSorting::UploadDate) => Some("CAISAhAD"),
+        (SearchType::Playlist, VideoSorting::ViewCount) => Some("CAMSAhAD"),
+        (SearchType::Playlist, VideoSorting::Rating) => Some("CAESAhAD"),
+    }
+}
+
+/// Which kinds of videos a search leaves out. Applied before `limit`, so a
+/// filtered search still returns up to `limit` videos.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoFilters {
+    pub min_duration_secs: Option<u64>,
+    pub max_duration_secs: Option<u64>,
+    pub exclude_shorts: bool,
+    /// Also drops upcoming premieres and scheduled streams.
+    pub exclude_live: bool,
+}
+
+impl VideoFilters {
+    fn validate(&self) -> Result<(), ServiceError> {
+        if let (Some(min), Some(max)) = (self.min_duration_secs, self.max_duration_secs)
+            && min > max
+        {
+            return Err(ServiceError::InvalidParams(
+                "min_duration_secs must not be greater than max_duration_secs".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Videos without a known duration (live streams, mostly) never pass a
+    /// duration bound.
+    fn matches(&self, video: &Video) -> bool {
+        if self.exclude_shorts && video.is_short {
+            return false;
+        }
+        if self.exclude_live && (video.is_live || video.is_upcoming) {
+            return false;
+        }
+        if self.min_duration_secs.is_some() || self.max_duration_secs.is_some() {
+            let Some(duration) = video.duration_secs else {
+                return false;
+            };
+            if self.min_duration_secs.is_some_and(|min| duration < min)
+                || self.max_duration_secs.is_some_and(|max| duration > max)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn cache_key_suffix(&self) -> String {
+        let mut suffix = String::new();
+        if self.min_duration_secs.is_some() || self.max_duration_secs.is_some() {
+            suffix.push_str(&format!(
+                "_d{}-{}",
+                self.min_duration_secs.map(|d| d.to_string()).unwrap_or_default(),
+                self.max_duration_secs.map(|d| d.to_string()).unwrap_or_default()
+            ));
+        }
+        if self.exclude_shorts {
+            suffix.push_str("_noshorts");
+        }
+        if self.exclude_live {
+            suffix.push_str("_nolive");
+        }
+        suffix
+    }
+}
+
+/// A page of search results, cached as a unit.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VideoPage {
+    pub videos: Arc<Vec<Video>>,
+    /// Opaque token for the page after this one, if there is one.
+    pub next_page_token: Option<String>,
+}
+
+impl cache::IsEmpty for VideoPage {
+    fn is_empty(&self) -> bool {
+        self.videos.is_empty()
+    }
+}
+
+impl VideoPage {
+    fn new(videos: Vec<Video>, next: Option<PageToken>) -> Self {
+        VideoPage {
+            videos: Arc::new(videos),
+            next_page_token: next.map(|token| token.encode()),
+        }
+    }
+}
+
+pub struct VideoResults {
+    pub page: Cached<VideoPage>,
+    /// YouTube could not be scraped and `page` holds the built-in fallback
+    /// list.
+    pub is_fallback: bool,
 }
 
-pub async fn handle_youtube_scraper(query: &str, limit: u32) -> Result<Vec<Video>, Box<dyn Error>> {
+pub async fn handle_youtube_scraper(
+    query: &str,
+    limit: u32,
+    sorting: VideoSorting,
+    search_type: SearchType,
+    filters: VideoFilters,
+    page_token: Option<&str>,
+) -> Result<VideoResults, ServiceError> {
     log::info!("Fetching YouTube data for: {}", query);
-    let videos = fetch_youtube_videos(query, limit).await.unwrap_or_else(|_| {
-        log::warn!("Failed to fetch videos for query: {}. Returning fallback videos.", query);
-        get_fallback_videos(query)
-    });
-    Ok(videos)
+    validate_search(query, limit)?;
+    filters.validate()?;
+    if search_type == SearchType::Playlist && filters != VideoFilters::default() {
+        return Err(ServiceError::InvalidParams(
+            "duration, Shorts and live filters only apply to video searches".to_string(),
+        ));
+    }
+    let search = VideoSearch {
+        query: query.to_string(),
+        limit,
+        sorting,
+        search_type,
+        filters,
+        page_token: page_token.map(|token| token.to_string()),
+    };
+    let cache_key = search.cache_key();
+    let origin = search.clone();
+    let result = cache::get_or_fetch(&cache_key, &origin, move || async move { search.fetch().await }).await;
+    // Only upstream failures on the first page of a video search fall back;
+    // the fallback list itself is never cached, so it can't be pinned.
+    match result {
+        Ok(page) => Ok(VideoResults {
+            page,
+            is_fallback: false,
+        }),
+        Err(e @ ServiceError::InvalidParams(_)) => Err(e),
+        Err(e) if page_token.is_some() || search_type == SearchType::Playlist => Err(e),
+        Err(_) => {
+            log::warn!("Failed to fetch videos for query: {}. Returning fallback videos.", query);
+            Ok(VideoResults {
+                page: Cached::fresh(VideoPage::new(get_fallback_videos(query), None)),
+                is_fallback: true,
+            })
+        }
+    }
+}
+
+/// Runs a video search upstream and caches the result even if a fresh entry
+/// already exists. Returns how many videos were found.
+pub async fn refresh_youtube_scraper(query: &str, limit: u32, sorting: VideoSorting) -> Result<usize, ServiceError> {
+    validate_search(query, limit)?;
+    VideoSearch {
+        query: query.to_string(),
+        limit,
+        sorting,
+        search_type: SearchType::Video,
+        filters: VideoFilters::default(),
+        page_token: None,
+    }
+    .refresh()
+    .await
+}
+
+/// Re-runs a video search from the parameters stored with its cache entry and
+/// returns how many videos it found. Unlike `handle_youtube_scraper`, a failed
+/// fetch is reported rather than replaced with fallback videos.
+pub async fn refresh_video_search(origin: serde_json::Value) -> Result<usize, ServiceError> {
+    let search: VideoSearch = serde_json::from_value(origin)?;
+    search.refresh().await
+}
+
+/// Video search parameters, stored as the cache entry's origin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VideoSearch {
+    query: String,
+    limit: u32,
+    sorting: VideoSorting,
+    #[serde(default)]
+    search_type: SearchType,
+    #[serde(default)]
+    filters: VideoFilters,
+    #[serde(default)]
+    page_token: Option<String>,
+}
+
+impl VideoSearch {
+    fn cache_key(&self) -> String {
+        let mut key = format!(
+            "videos_{}_{}_{}",
+            self.query.to_lowercase().replace(" ", "_"),
+            self.limit,
+            self.sorting.as_str()
+        );
+        if self.search_type == SearchType::Playlist {
+            key.push_str("_playlists");
+        }
+        key.push_str(&self.filters.cache_key_suffix());
+        if let Some(token) = &self.page_token {
+            key.push('_');
+            key.push_str(token);
+        }
+        key
+    }
+
+    async fn refresh(self) -> Result<usize, ServiceError> {
+        let cache_key = self.cache_key();
+        let origin = self.clone();
+        let page: Arc<VideoPage> = cache::refresh(&cache_key, &origin, move || async move { self.fetch().await }).await?;
+        Ok(page.videos.len())
+    }
+
+    async fn fetch(&self) -> Result<VideoPage, ServiceError> {
+        fetch_youtube_videos(
+            &self.query,
+            self.limit,
+            self.sorting,
+            self.search_type,
+            &self.filters,
+            self.page_token.as_deref(),
+        )
+        .await
+    }
+}
+
+/// One page of search results and the continuation token for the page after
+/// it, as YouTube returns them.
+struct SearchPage {
+    videos: Vec<Video>,
+    continuation: Option<String>,
+}
+
+/// Where a page of results starts: `skip` videos into the page fetched with
+/// `continuation`, or into the HTML results page when there is none. Handed to
+/// clients as an opaque `page_token` of the form `<skip>.<continuation>`.
+struct PageToken {
+    continuation: Option<String>,
+    skip: usize,
+}
+
+impl PageToken {
+    fn parse(token: &str) -> Result<Self, ServiceError> {
+        let invalid = || ServiceError::InvalidParams("invalid page_token".to_string());
+        let (skip, continuation) = token.split_once('.').ok_or_else(invalid)?;
+        Ok(PageToken {
+            continuation: Some(continuation).filter(|c| !c.is_empty()).map(|c| c.to_string()),
+            skip: skip.parse().map_err(|_| invalid())?,
+        })
+    }
+
+    fn encode(&self) -> String {
+        format!("{}.{}", self.skip, self.continuation.as_deref().unwrap_or(""))
+    }
+}
+
+// Upper bound on upstream requests per search, whatever the limit.
+const MAX_SEARCH_PAGES: usize = 10;
+// Consecutive pages without a video passing the filters after which a search
+// stops looking; filters that match nothing would otherwise use up all of
+// `MAX_SEARCH_PAGES`.
+const MAX_FILTERED_OUT_PAGES: usize = 3;
+
+/// Collects `limit` videos passing `filters`, starting at `page_token` and
+/// following continuation tokens as needed. Page token offsets count filtered
+/// videos, so they are only valid with the same filters.
+async fn fetch_youtube_videos(
+    query: &str,
+    limit: u32,
+    sorting: VideoSorting,
+    search_type: SearchType,
+    filters: &VideoFilters,
+    page_token: Option<&str>,
+) -> Result<VideoPage, ServiceError> {
+    let start = match page_token {
+        Some(token) => PageToken::parse(token)?,
+        None => PageToken {
+            continuation: None,
+            skip: 0,
+        },
+    };
+    let client = &youtube_client()?;
+    let collected = collect_videos(start, limit as usize, filters, |continuation| async move {
+        match continuation {
+            Some(continuation) => fetch_continuation_page(client, &continuation, search_type).await,
+            None => fetch_search_page(client, query, sorting, search_type).await,
+        }
+    })
+    .await?;
+
+    // A first page without a single result means the page layout wasn't
+    // understood; results that were all filtered out are just an empty page.
+    if collected.first_page_empty && page_token.is_none() {
+        return Err(ServiceError::Parse(format!("No {}s found in search results", search_type.as_str())));
+    }
+    log::info!("Fetched {} {}s for query: {}", collected.videos.len(), search_type.as_str(), query);
+    Ok(VideoPage::new(collected.videos, collected.next))
+}
+
+/// What `collect_videos` gathered.
+struct Collected {
+    videos: Vec<Video>,
+    /// Where the page after `videos` starts.
+    next: Option<PageToken>,
+    /// The first upstream page had no results at all, before filtering.
+    first_page_empty: bool,
+}
+
+/// Assembles one page of results from `start` on, reading upstream pages with
+/// `fetch_page` (given the continuation token, or `None` for the first page).
+/// Gives up early once `MAX_FILTERED_OUT_PAGES` pages in a row had nothing
+/// passing `filters`.
+async fn collect_videos<F, Fut>(
+    start: PageToken,
+    limit: usize,
+    filters: &VideoFilters,
+    mut fetch_page: F,
+) -> Result<Collected, ServiceError>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<SearchPage, ServiceError>>,
+{
+    let mut current = start.continuation;
+    let mut skip = start.skip;
+    let mut videos = Vec::new();
+    let mut next = None;
+    let mut first_page_empty = false;
+    let mut filtered_out_pages = 0;
+
+    for page_number in 0..MAX_SEARCH_PAGES {
+        let page = fetch_page(current.clone()).await?;
+        if page_number == 0 {
+            first_page_empty = page.videos.is_empty();
+        }
+        let page_videos: Vec<Video> = page.videos.into_iter().filter(|video| filters.matches(video)).collect();
+        if page_videos.is_empty() {
+            filtered_out_pages += 1;
+        } else {
+            filtered_out_pages = 0;
+        }
+        let needed = limit - videos.len();
+        let available = page_videos.len().saturating_sub(skip);
+        if available > needed {
+            // Stop partway through this page; the next request resumes here.
+            videos.extend(page_videos.into_iter().skip(skip).take(needed));
+            next = Some(PageToken {
+                continuation: current,
+                skip: skip + needed,
+            });
+            break;
+        }
+
+        videos.extend(page_videos.into_iter().skip(skip));
+        skip = 0;
+        next = page.continuation.map(|continuation| PageToken {
+            continuation: Some(continuation),
+            skip: 0,
+        });
+        match &next {
+            Some(token) if videos.len() < limit && filtered_out_pages < MAX_FILTERED_OUT_PAGES => {
+                current = token.continuation.clone()
+            }
+            _ => break,
+        }
+    }
+    Ok(Collected {
+        videos,
+        next,
+        first_page_empty,
+    })
 }
 
-async fn fetch_youtube_videos(query: &str, limit: u32) -> Result<Vec<Video>, Box<dyn Error>> {
-    let search_url = format!(
+pub(crate) fn youtube_client() -> Result<reqwest::Client, ServiceError> {
+    Ok(reqwest::Client::builder()
+        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?)
+}
+
+async fn fetch_search_page(
+    client: &reqwest::Client,
+    query: &str,
+    sorting: VideoSorting,
+    search_type: SearchType,
+) -> Result<SearchPage, ServiceError> {
+    let mut search_url = format!(
         "https://www.youtube.com/results?search_query={}+tutorial",
         encode(query)
     );
+    if let Some(filter) = search_filter(sorting, search_type) {
+        search_url.push_str("&sp=");
+        search_url.push_str(filter);
+    }
     log::info!("Fetching YouTube URL: {}", search_url);
 
-    let client = reqwest::Client::builder()
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
-        .timeout(std::time::Duration::from_secs(10))
-        .build()?;
-
     let response = client
         .get(&search_url)
         .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
@@ -48,100 +533,316 @@ async fn fetch_youtube_videos(query: &str, limit: u32) -> Result<Vec<Video>, Box
         .await?;
 
     if !response.status().is_success() {
-        return Err(format!("YouTube request failed with status: {}", response.status()).into());
+        return Err(ServiceError::from_status("YouTube", response.status()));
     }
 
     let body = response.text().await?;
     log::debug!("YouTube response length: {} bytes", body.len());
 
-    let videos = extract_videos_from_html(&body, query, limit)?;
-    if videos.is_empty() {
-        return Err("No videos found".into());
-    }
-
-    log::info!("Fetched {} videos for query: {}", videos.len(), query);
-    Ok(videos)
-}
-
-fn extract_videos_from_html(html: &str, _query: &str, limit: u32) -> Result<Vec<Video>, Box<dyn Error>> {
-    let json_start = html.find("var ytInitialData = ").ok_or("Could not find ytInitialData")?;
-    let json_end = html[json_start..].find(";</script>").ok_or("Could not find end of JSON")?;
-    let json_str = &html[json_start + 19..json_start + json_end];
+    let json_start = body
+        .find("var ytInitialData = ")
+        .ok_or_else(|| ServiceError::Parse("Could not find ytInitialData".to_string()))?;
+    let json_end = body[json_start..]
+        .find(";</script>")
+        .ok_or_else(|| ServiceError::Parse("Could not find end of JSON".to_string()))?;
+    let json_data: serde_json::Value = serde_json::from_str(&body[json_start + 19..json_start + json_end])?;
 
-    let json_data: serde_json::Value = serde_json::from_str(json_str)?;
-    let contents = json_data
+    let sections = json_data
         .get("contents")
         .and_then(|c| c.get("twoColumnSearchResultsRenderer"))
         .and_then(|r| r.get("primaryContents"))
         .and_then(|p| p.get("sectionListRenderer"))
         .and_then(|s| s.get("contents"))
-        .and_then(|c| c.get(0))
-        .and_then(|c| c.get("itemSectionRenderer"))
-        .and_then(|i| i.get("contents"))
-        .ok_or("Could not find video contents in JSON")?;
+        .and_then(|c| c.as_array())
+        .ok_or_else(|| ServiceError::Parse("Could not find video contents in JSON".to_string()))?;
+    Ok(parse_search_sections(sections, search_type))
+}
 
-    let mut videos = Vec::new();
-    if let serde_json::Value::Array(items) = contents {
-        for item in items {
-            if let Some(video_renderer) = item.get("videoRenderer") {
-                let video_id = video_renderer
-                    .get("videoId")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let title = video_renderer
-                    .get("title")
-                    .and_then(|t| t.get("runs"))
-                    .and_then(|r| r.get(0))
-                    .and_then(|r| r.get("text"))
-                    .and_then(|t| t.as_str())
-                    .unwrap_or("");
-                let image = video_renderer
-                    .get("thumbnail")
-                    .and_then(|t| t.get("thumbnails"))
-                    .and_then(|t| t.get(0))
-                    .and_then(|t| t.get("url"))
-                    .and_then(|u| u.as_str())
-                    .map(|url| url.split('?').next().unwrap_or(url))
-                    .unwrap_or("");
-
-                if video_id.is_empty() || title.is_empty() {
-                    continue;
-                }
-
-                let url = format!("https://www.youtube.com/watch?v={}", video_id);
-                let difficulty = determine_difficulty(title);
-
-                videos.push(Video {
-                    title: title.to_string(),
-                    url,
-                    video_id: video_id.to_string(),
-                    r#type: "video".to_string(),
-                    free: true,
-                    image: image.to_string(),
-                    source: "YouTube".to_string(),
-                    difficulty,
-                    description: "".to_string(),
-                });
-
-                if videos.len() >= limit as usize {
-                    break;
-                }
+// Client version sent with innertube requests. Continuations are fetched
+// without loading the HTML page first, so this can't be read from it.
+const INNERTUBE_CLIENT_VERSION: &str = "2.20240814.00.00";
+
+/// Requests the next page behind a continuation token from one of YouTube's
+/// internal API endpoints (`search`, `browse`).
+pub(crate) async fn fetch_continuation(
+    client: &reqwest::Client,
+    endpoint: &str,
+    continuation: &str,
+) -> Result<reqwest::Response, ServiceError> {
+    let body = serde_json::json!({
+        "context": {
+            "client": {
+                "clientName": "WEB",
+                "clientVersion": INNERTUBE_CLIENT_VERSION,
+                "hl": "en",
             }
+        },
+        "continuation": continuation,
+    });
+    Ok(client
+        .post(format!("https://www.youtube.com/youtubei/v1/{}?prettyPrint=false", endpoint))
+        .json(&body)
+        .send()
+        .await?)
+}
+
+async fn fetch_continuation_page(
+    client: &reqwest::Client,
+    continuation: &str,
+    search_type: SearchType,
+) -> Result<SearchPage, ServiceError> {
+    log::info!("Fetching YouTube search continuation");
+    let response = fetch_continuation(client, "search", continuation).await?;
+
+    // YouTube rejects unknown or expired tokens with a 400.
+    if response.status() == reqwest::StatusCode::BAD_REQUEST {
+        return Err(ServiceError::InvalidParams("invalid or expired page_token".to_string()));
+    }
+    if !response.status().is_success() {
+        return Err(ServiceError::from_status("YouTube", response.status()));
+    }
+
+    let json_data: serde_json::Value = response.json().await?;
+    let sections = json_data
+        .get("onResponseReceivedCommands")
+        .and_then(|c| c.as_array())
+        .and_then(|commands| {
+            commands.iter().find_map(|command| {
+                command
+                    .get("appendContinuationItemsAction")
+                    .and_then(|a| a.get("continuationItems"))
+                    .and_then(|items| items.as_array())
+            })
+        })
+        .ok_or_else(|| ServiceError::Parse("Could not find continuation items in JSON".to_string()))?;
+    Ok(parse_search_sections(sections, search_type))
+}
+
+/// Reads the videos or playlists and the next continuation token out of a
+/// search result section list; the HTML page and continuation responses share
+/// this shape. Shorts shelves (`reelShelfRenderer`) and items of the other
+/// type are skipped.
+fn parse_search_sections(sections: &[serde_json::Value], search_type: SearchType) -> SearchPage {
+    let mut videos = Vec::new();
+    let mut continuation = None;
+    for section in sections {
+        if let Some(items) = section
+            .get("itemSectionRenderer")
+            .and_then(|i| i.get("contents"))
+            .and_then(|c| c.as_array())
+        {
+            videos.extend(items.iter().filter_map(|item| match search_type {
+                SearchType::Video => item.get("videoRenderer").and_then(parse_video_renderer),
+                SearchType::Playlist => item.get("playlistRenderer").and_then(parse_playlist_renderer),
+            }));
+        } else if let Some(token) = section
+            .get("continuationItemRenderer")
+            .and_then(|c| c.get("continuationEndpoint"))
+            .and_then(|e| e.get("continuationCommand"))
+            .and_then(|c| c.get("token"))
+            .and_then(|t| t.as_str())
+        {
+            continuation = Some(token.to_string());
         }
     }
+    SearchPage { videos, continuation }
+}
+
+fn parse_video_renderer(renderer: &serde_json::Value) -> Option<Video> {
+    let video_id = renderer.get("videoId").and_then(|v| v.as_str()).filter(|id| !id.is_empty())?;
+    let title = text(renderer.get("title")).filter(|title| !title.is_empty())?;
+    let owner = renderer.get("ownerText");
+    let description = renderer
+        .get("detailedMetadataSnippets")
+        .and_then(|s| s.get(0))
+        .and_then(|s| text(s.get("snippetText")))
+        .or_else(|| text(renderer.get("descriptionSnippet")))
+        .unwrap_or_default();
+    let published_text = text(renderer.get("publishedTimeText"));
+    let published_date = published_text
+        .as_deref()
+        .and_then(|published| approximate_publish_date(published, Utc::now()));
+    // The time overlay on the thumbnail says LIVE, UPCOMING or SHORTS instead
+    // of a length for those kinds of video.
+    let overlay_style = renderer
+        .get("thumbnailOverlays")
+        .and_then(|o| o.as_array())
+        .and_then(|overlays| {
+            overlays.iter().find_map(|overlay| {
+                overlay
+                    .get("thumbnailOverlayTimeStatusRenderer")
+                    .and_then(|r| r.get("style"))
+                    .and_then(|s| s.as_str())
+            })
+        });
+    let has_badge = |style: &str| {
+        renderer
+            .get("badges")
+            .and_then(|b| b.as_array())
+            .is_some_and(|badges| {
+                badges.iter().any(|badge| {
+                    badge
+                        .get("metadataBadgeRenderer")
+                        .and_then(|r| r.get("style"))
+                        .and_then(|s| s.as_str())
+                        == Some(style)
+                })
+            })
+    };
+    let watch_path = renderer
+        .get("navigationEndpoint")
+        .and_then(|n| n.get("commandMetadata"))
+        .and_then(|c| c.get("webCommandMetadata"))
+        .and_then(|w| w.get("url"))
+        .and_then(|u| u.as_str())
+        .unwrap_or("");
+
+    Some(Video {
+        url: format!("https://www.youtube.com/watch?v={}", video_id),
+        video_id: video_id.to_string(),
+        r#type: "video".to_string(),
+        free: true,
+        image: thumbnail_url(renderer.get("thumbnail")),
+        source: "YouTube".to_string(),
+        difficulty: determine_difficulty(&title),
+        title,
+        description,
+        channel_name: text(owner),
+        channel_id: channel_id(owner),
+        duration_secs: text(renderer.get("lengthText")).and_then(|length| parse_duration(&length)),
+        view_count: text(renderer.get("viewCountText")).and_then(|views| parse_view_count(&views)),
+        published_text,
+        published_date,
+        is_short: overlay_style == Some("SHORTS") || watch_path.starts_with("/shorts/"),
+        is_live: overlay_style == Some("LIVE") || has_badge("BADGE_STYLE_TYPE_LIVE_NOW"),
+        is_upcoming: overlay_style == Some("UPCOMING") || renderer.get("upcomingEventData").is_some(),
+        playlist_id: None,
+        video_count: None,
+    })
+}
+
+fn parse_playlist_renderer(renderer: &serde_json::Value) -> Option<Video> {
+    let playlist_id = renderer.get("playlistId").and_then(|v| v.as_str()).filter(|id| !id.is_empty())?;
+    let title = text(renderer.get("title")).filter(|title| !title.is_empty())?;
+    let owner = renderer.get("longBylineText").or_else(|| renderer.get("shortBylineText"));
+    let first_video_id = renderer
+        .get("navigationEndpoint")
+        .and_then(|n| n.get("watchEndpoint"))
+        .and_then(|w| w.get("videoId"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let published_text = text(renderer.get("publishedTimeText"));
+
+    Some(Video {
+        url: format!("https://www.youtube.com/playlist?list={}", playlist_id),
+        video_id: first_video_id.to_string(),
+        r#type: "playlist".to_string(),
+        free: true,
+        image: thumbnail_url(renderer.get("thumbnails").and_then(|t| t.get(0))),
+        source: "YouTube".to_string(),
+        difficulty: determine_difficulty(&title),
+        title,
+        description: String::new(),
+        channel_name: text(owner),
+        channel_id: channel_id(owner),
+        duration_secs: None,
+        view_count: None,
+        published_date: published_text
+            .as_deref()
+            .and_then(|published| approximate_publish_date(published, Utc::now())),
+        published_text,
+        is_short: false,
+        is_live: false,
+        is_upcoming: false,
+        playlist_id: Some(playlist_id.to_string()),
+        video_count: renderer
+            .get("videoCount")
+            .and_then(|c| c.as_str())
+            .and_then(parse_view_count),
+    })
+}
+
+/// First URL of a `thumbnail` object's `thumbnails` list, without its query
+/// string.
+pub(crate) fn thumbnail_url(thumbnail: Option<&serde_json::Value>) -> String {
+    thumbnail
+        .and_then(|t| t.get("thumbnails"))
+        .and_then(|t| t.get(0))
+        .and_then(|t| t.get("url"))
+        .and_then(|u| u.as_str())
+        .map(|url| url.split('?').next().unwrap_or(url))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Channel ID linked from a byline such as `ownerText`.
+pub(crate) fn channel_id(owner: Option<&serde_json::Value>) -> Option<String> {
+    owner
+        .and_then(|o| o.get("runs"))
+        .and_then(|r| r.get(0))
+        .and_then(|r| r.get("navigationEndpoint"))
+        .and_then(|n| n.get("browseEndpoint"))
+        .and_then(|b| b.get("browseId"))
+        .and_then(|id| id.as_str())
+        .map(|id| id.to_string())
+}
+
+/// Flattens one of YouTube's text objects, which hold either a `simpleText`
+/// string or a list of formatted `runs`.
+pub(crate) fn text(value: Option<&serde_json::Value>) -> Option<String> {
+    let value = value?;
+    if let Some(simple) = value.get("simpleText").and_then(|t| t.as_str()) {
+        return Some(simple.to_string());
+    }
+    let runs = value.get("runs")?.as_array()?;
+    Some(runs.iter().filter_map(|run| run.get("text").and_then(|t| t.as_str())).collect())
+}
 
-    Ok(videos)
+/// Parses a `lengthText` such as "12:34" or "1:02:03" into seconds.
+pub(crate) fn parse_duration(length: &str) -> Option<u64> {
+    length
+        .trim()
+        .split(':')
+        .try_fold(0u64, |secs, part| Some(secs * 60 + part.parse::<u64>().ok()?))
+}
+
+/// Parses a `viewCountText` such as "1,234,567 views" or "No views".
+pub(crate) fn parse_view_count(views: &str) -> Option<u64> {
+    if views.trim().eq_ignore_ascii_case("no views") {
+        return Some(0);
+    }
+    let digits: String = views.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Turns a relative `publishedTimeText` ("3 weeks ago", "Streamed 2 days ago")
+/// into an approximate YYYY-MM-DD date. Months and years are taken as 30 and
+/// 365 days.
+pub(crate) fn approximate_publish_date(published: &str, now: DateTime<Utc>) -> Option<String> {
+    let captures = PUBLISHED_AGO.captures(published)?;
+    let amount: i32 = captures[1].parse().ok()?;
+    let unit = match &captures[2] {
+        "second" => ChronoDuration::seconds(1),
+        "minute" => ChronoDuration::minutes(1),
+        "hour" => ChronoDuration::hours(1),
+        "day" => ChronoDuration::days(1),
+        "week" => ChronoDuration::weeks(1),
+        "month" => ChronoDuration::days(30),
+        _ => ChronoDuration::days(365),
+    };
+    let published = now.checked_sub_signed(unit.checked_mul(amount)?)?;
+    Some(published.format("%Y-%m-%d").to_string())
 }
 
 fn get_fallback_videos(query: &str) -> Vec<Video> {
-    let fallback_data = vec![
+    let fallback_data = [
         ("docker", "Docker Tutorial for Beginners", "3c-iBn73dDE"),
         ("rust programming", "Rust Programming Course for Beginners", "MsocPEZBd-M"),
         ("python", "Python Tutorial for Beginners", "rfscVS0vtbw"),
         ("javascript", "JavaScript Tutorial for Beginners", "W6NZfCO5SIk"),
     ];
 
-    let fallback = fallback_data
+    fallback_data
         .iter()
         .find(|(q, _, _)| query.to_lowercase().contains(q))
         .map(|(_, title, video_id)| {
@@ -158,6 +859,17 @@ fn get_fallback_videos(query: &str) -> Vec<Video> {
                 source: "YouTube".to_string(),
                 difficulty,
                 description: "".to_string(),
+                channel_name: None,
+                channel_id: None,
+                duration_secs: None,
+                view_count: None,
+                published_text: None,
+                published_date: None,
+                is_short: false,
+                is_live: false,
+                is_upcoming: false,
+                playlist_id: None,
+                video_count: None,
             }]
         })
         .unwrap_or_else(|| {
@@ -171,13 +883,22 @@ fn get_fallback_videos(query: &str) -> Vec<Video> {
                 source: "YouTube".to_string(),
                 difficulty: "beginner".to_string(),
                 description: "".to_string(),
+                channel_name: None,
+                channel_id: None,
+                duration_secs: None,
+                view_count: None,
+                published_text: None,
+                published_date: None,
+                is_short: false,
+                is_live: false,
+                is_upcoming: false,
+                playlist_id: None,
+                video_count: None,
             }]
-        });
-
-    fallback
+        })
 }
 
-fn determine_difficulty(title: &str) -> String {
+pub(crate) fn determine_difficulty(title: &str) -> String {
     let lower_title = title.to_lowercase();
     if lower_title.contains("beginner") || lower_title.contains("basics") || lower_title.contains("introduction") || lower_title.contains("101") {
         "beginner".to_string()
@@ -187,3 +908,264 @@ fn determine_difficulty(title: &str) -> String {
         "intermediate".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(id: &str) -> Video {
+        Video {
+            title: id.to_string(),
+            url: format!("https://www.youtube.com/watch?v={}", id),
+            video_id: id.to_string(),
+            r#type: "video".to_string(),
+            free: true,
+            image: String::new(),
+            source: "YouTube".to_string(),
+            difficulty: "intermediate".to_string(),
+            description: String::new(),
+            channel_name: None,
+            channel_id: None,
+            duration_secs: None,
+            view_count: None,
+            published_text: None,
+            published_date: None,
+            is_short: false,
+            is_live: false,
+            is_upcoming: false,
+            playlist_id: None,
+            video_count: None,
+        }
+    }
+
+    /// Upstream pages keyed by the continuation that fetches them; the first
+    /// page has none. Each page's videos are `<key>0`, `<key>1`, ...
+    fn pages(counts: &[usize]) -> Vec<(Option<String>, SearchPage)> {
+        (0..counts.len())
+            .map(|i| {
+                let key = (i > 0).then(|| format!("c{}", i));
+                let prefix = key.clone().unwrap_or_else(|| "c0".to_string());
+                let page = SearchPage {
+                    videos: (0..counts[i]).map(|n| video(&format!("{}_{}", prefix, n))).collect(),
+                    continuation: (i + 1 < counts.len()).then(|| format!("c{}", i + 1)),
+                };
+                (key, page)
+            })
+            .collect()
+    }
+
+    /// Runs `collect_videos` over `pages` and returns the video IDs, the next
+    /// page token and the continuations fetched.
+    async fn collect(
+        pages: Vec<(Option<String>, SearchPage)>,
+        page_token: Option<&str>,
+        limit: usize,
+    ) -> (Vec<String>, Option<String>, Vec<Option<String>>) {
+        collect_filtered(pages, page_token, limit, &VideoFilters::default()).await
+    }
+
+    async fn collect_filtered(
+        pages: Vec<(Option<String>, SearchPage)>,
+        page_token: Option<&str>,
+        limit: usize,
+        filters: &VideoFilters,
+    ) -> (Vec<String>, Option<String>, Vec<Option<String>>) {
+        let mut pages = pages;
+        let mut fetched = Vec::new();
+        let start = match page_token {
+            Some(token) => PageToken::parse(token).unwrap(),
+            None => PageToken {
+                continuation: None,
+                skip: 0,
+            },
+        };
+        let collected = collect_videos(start, limit, filters, |continuation| {
+            fetched.push(continuation.clone());
+            let index = pages.iter().position(|(key, _)| *key == continuation).unwrap();
+            let page = pages.remove(index).1;
+            async move { Ok(page) }
+        })
+        .await
+        .unwrap();
+        (
+            collected.videos.into_iter().map(|video| video.video_id).collect(),
+            collected.next.map(|token| token.encode()),
+            fetched,
+        )
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn stops_partway_through_a_page() {
+        let (videos, next, fetched) = collect(pages(&[5, 5]), None, 3).await;
+        assert_eq!(videos, ids(&["c0_0", "c0_1", "c0_2"]));
+        assert_eq!(next.as_deref(), Some("3."));
+        assert_eq!(fetched, vec![None]);
+    }
+
+    #[tokio::test]
+    async fn exactly_filling_a_page_continues_on_the_next() {
+        let (videos, next, fetched) = collect(pages(&[3, 5]), None, 3).await;
+        assert_eq!(videos, ids(&["c0_0", "c0_1", "c0_2"]));
+        assert_eq!(next.as_deref(), Some("0.c1"));
+        assert_eq!(fetched, vec![None]);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_a_skip_token() {
+        let (videos, next, fetched) = collect(pages(&[5, 4]), Some("3."), 4).await;
+        assert_eq!(videos, ids(&["c0_3", "c0_4", "c1_0", "c1_1"]));
+        assert_eq!(next.as_deref(), Some("2.c1"));
+        assert_eq!(fetched, vec![None, Some("c1".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_a_continuation_token() {
+        let (videos, next, fetched) = collect(pages(&[5, 4, 4]), Some("2.c1"), 3).await;
+        assert_eq!(videos, ids(&["c1_2", "c1_3", "c2_0"]));
+        assert_eq!(next.as_deref(), Some("1.c2"));
+        assert_eq!(fetched, vec![Some("c1".to_string()), Some("c2".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn ends_without_a_token_on_the_last_page() {
+        let (videos, next, _) = collect(pages(&[2, 2]), None, 10).await;
+        assert_eq!(videos, ids(&["c0_0", "c0_1", "c1_0", "c1_1"]));
+        assert_eq!(next, None);
+    }
+
+    #[tokio::test]
+    async fn stops_when_filters_match_nothing() {
+        let mut pages = pages(&[5, 5, 5, 5, 5]);
+        for (_, page) in &mut pages {
+            page.videos.iter_mut().for_each(|video| video.is_short = true);
+        }
+        let filters = VideoFilters {
+            exclude_shorts: true,
+            ..VideoFilters::default()
+        };
+        let (videos, next, fetched) = collect_filtered(pages, None, 10, &filters).await;
+        assert!(videos.is_empty());
+        assert_eq!(next.as_deref(), Some("0.c3"));
+        assert_eq!(fetched.len(), MAX_FILTERED_OUT_PAGES);
+    }
+
+    fn renderer() -> serde_json::Value {
+        serde_json::json!({
+            "videoId": "abcdefghijk",
+            "title": {"runs": [{"text": "Rust for "}, {"text": "Beginners"}]},
+            "ownerText": {"runs": [{
+                "text": "Some Channel",
+                "navigationEndpoint": {"browseEndpoint": {"browseId": "UCxxxxxxxxxxxxxxxxxxxxxx"}}
+            }]},
+            "detailedMetadataSnippets": [{"snippetText": {"runs": [{"text": "Learn Rust"}]}}],
+            "publishedTimeText": {"simpleText": "3 weeks ago"},
+            "lengthText": {"simpleText": "1:02:03"},
+            "viewCountText": {"simpleText": "1,234,567 views"},
+            "thumbnail": {"thumbnails": [{"url": "https://i.ytimg.com/vi/abcdefghijk/hq720.jpg?sqp=x"}]},
+            "thumbnailOverlays": [{"thumbnailOverlayTimeStatusRenderer": {"style": "DEFAULT"}}],
+            "navigationEndpoint": {"commandMetadata": {"webCommandMetadata": {"url": "/watch?v=abcdefghijk"}}}
+        })
+    }
+
+    #[test]
+    fn parses_a_video_renderer() {
+        let video = parse_video_renderer(&renderer()).unwrap();
+        assert_eq!(video.video_id, "abcdefghijk");
+        assert_eq!(video.title, "Rust for Beginners");
+        assert_eq!(video.difficulty, "beginner");
+        assert_eq!(video.description, "Learn Rust");
+        assert_eq!(video.channel_name.as_deref(), Some("Some Channel"));
+        assert_eq!(video.channel_id.as_deref(), Some("UCxxxxxxxxxxxxxxxxxxxxxx"));
+        assert_eq!(video.duration_secs, Some(3723));
+        assert_eq!(video.view_count, Some(1_234_567));
+        assert_eq!(video.image, "https://i.ytimg.com/vi/abcdefghijk/hq720.jpg");
+        assert!(!video.is_short && !video.is_live && !video.is_upcoming);
+    }
+
+    #[test]
+    fn detects_shorts_from_the_overlay_or_the_url() {
+        let mut shorts = renderer();
+        shorts["thumbnailOverlays"][0]["thumbnailOverlayTimeStatusRenderer"]["style"] = "SHORTS".into();
+        assert!(parse_video_renderer(&shorts).unwrap().is_short);
+
+        let mut shorts = renderer();
+        shorts["navigationEndpoint"]["commandMetadata"]["webCommandMetadata"]["url"] = "/shorts/abcdefghijk".into();
+        assert!(parse_video_renderer(&shorts).unwrap().is_short);
+    }
+
+    #[test]
+    fn detects_live_and_upcoming_videos() {
+        let mut live = renderer();
+        live["badges"] = serde_json::json!([{"metadataBadgeRenderer": {"style": "BADGE_STYLE_TYPE_LIVE_NOW"}}]);
+        let live = parse_video_renderer(&live).unwrap();
+        assert!(live.is_live && !live.is_upcoming);
+
+        let mut upcoming = renderer();
+        upcoming["thumbnailOverlays"][0]["thumbnailOverlayTimeStatusRenderer"]["style"] = "UPCOMING".into();
+        let upcoming = parse_video_renderer(&upcoming).unwrap();
+        assert!(upcoming.is_upcoming && !upcoming.is_live);
+    }
+
+    #[test]
+    fn skips_renderers_without_an_id_or_title() {
+        let mut no_id = renderer();
+        no_id["videoId"] = "".into();
+        assert!(parse_video_renderer(&no_id).is_none());
+        let mut no_title = renderer();
+        no_title.as_object_mut().unwrap().remove("title");
+        assert!(parse_video_renderer(&no_title).is_none());
+    }
+
+    #[test]
+    fn parses_durations() {
+        for (length, secs) in [
+            ("1:02:03", Some(3723)),
+            ("12:34", Some(754)),
+            ("0:05", Some(5)),
+            (" 3:00 ", Some(180)),
+            ("LIVE", None),
+            ("", None),
+        ] {
+            assert_eq!(parse_duration(length), secs, "{:?}", length);
+        }
+    }
+
+    #[test]
+    fn parses_view_counts() {
+        for (views, count) in [
+            ("1,234,567 views", Some(1_234_567)),
+            ("1 view", Some(1)),
+            ("No views", Some(0)),
+            ("no views", Some(0)),
+            ("views", None),
+        ] {
+            assert_eq!(parse_view_count(views), count, "{:?}", views);
+        }
+    }
+
+    #[test]
+    fn approximates_publish_dates() {
+        let now = DateTime::parse_from_rfc3339("2024-03-15T12:00:00Z").unwrap().with_timezone(&Utc);
+        for (published, date) in [
+            ("Streamed 2 days ago", Some("2024-03-13")),
+            ("1 week ago", Some("2024-03-08")),
+            ("3 months ago", Some("2023-12-16")),
+            ("2 years ago", Some("2022-03-16")), // 730 days, across a leap day
+            ("5 hours ago", Some("2024-03-15")),
+            ("Premieres tomorrow", None),
+        ] {
+            assert_eq!(approximate_publish_date(published, now).as_deref(), date, "{:?}", published);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_page_tokens() {
+        assert!(PageToken::parse("abc").is_err());
+        assert!(PageToken::parse("x.c1").is_err());
+        assert_eq!(PageToken::parse("4.c1").unwrap().encode(), "4.c1");
+    }
+}
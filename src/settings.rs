@@ -0,0 +1,203 @@
+use config::{Config, ConfigError, Environment, File};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::time::Duration;
+
+lazy_static! {
+    static ref SETTINGS: Settings = Settings::load().expect("Failed to load configuration");
+}
+
+/// Returns the process-wide settings, loaded from `hyper_fetch.{toml,yaml,json}`
+/// (optional) and `HYPER_FETCH_*` environment variables, e.g.
+/// `HYPER_FETCH_CACHE__BACKEND=redis`.
+pub fn get() -> &'static Settings {
+    &SETTINGS
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub cache: CacheSettings,
+    pub admin: AdminSettings,
+    pub warmup: WarmupSettings,
+    pub jobs: JobsSettings,
+    pub channels: ChannelSettings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChannelSettings {
+    /// Channel IDs (`UC...`) whose feeds are searched when a request names no
+    /// channels, e.g. a list of trusted educational channels.
+    pub curated: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobsSettings {
+    /// How often the shared RemoteOK feed is re-downloaded in the background;
+    /// 0 disables the background refresh.
+    pub feed_refresh_secs: u64,
+}
+
+impl Default for JobsSettings {
+    fn default() -> Self {
+        JobsSettings {
+            feed_refresh_secs: 10 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AdminSettings {
+    /// Shared secret for the cache admin endpoints, sent as
+    /// `Authorization: Bearer <token>` or `X-Admin-Token: <token>`. Admin
+    /// endpoints reject every request while this is unset.
+    pub token: Option<String>,
+}
+
+/// Searches refreshed on a schedule so they are always served from cache.
+/// Limits match the API defaults, so warmed entries are the ones a request
+/// without an explicit `limit` hits. Job searches all read the RemoteOK feed,
+/// so listing any of them keeps that feed warm.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WarmupSettings {
+    /// 0 disables warming.
+    pub interval_secs: u64,
+    pub jobs: Vec<String>,
+    pub videos: Vec<String>,
+    pub videos_limit: u32,
+}
+
+impl Default for WarmupSettings {
+    fn default() -> Self {
+        WarmupSettings {
+            interval_secs: 30 * 60,
+            jobs: Vec::new(),
+            videos: Vec::new(),
+            videos_limit: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackendKind {
+    #[default]
+    Memory,
+    Redis,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheSettings {
+    pub backend: CacheBackendKind,
+    pub redis_url: String,
+    pub redis_prefix: String,
+    /// Limits for the in-memory backend; least recently used entries are
+    /// evicted once either is exceeded.
+    pub max_entries: usize,
+    pub max_bytes: usize,
+    /// How often expired entries are purged; 0 disables the sweeper.
+    pub sweep_interval_secs: u64,
+    pub ttl: TtlSettings,
+    /// How long past its TTL an entry may still be served while a background
+    /// refresh runs.
+    pub stale_grace_secs: u64,
+    /// Where the in-memory backend persists its entries across restarts.
+    /// Snapshots are disabled when unset.
+    pub snapshot_path: Option<String>,
+    /// How often the snapshot is rewritten; 0 only writes it on shutdown.
+    pub snapshot_interval_secs: u64,
+}
+
+/// Cache lifetimes in seconds, per key namespace. `empty` and `error` apply
+/// to negative entries in every namespace.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TtlSettings {
+    pub default: u64,
+    pub videos: u64,
+    /// Single video details scraped from the watch page.
+    pub watch: u64,
+    /// Video captions.
+    pub transcript: u64,
+    /// Playlists expanded into their videos.
+    pub playlist: u64,
+    /// A channel's RSS feed of its latest videos.
+    pub channel: u64,
+    /// The raw RemoteOK feed every job query filters.
+    pub feed: u64,
+    pub empty: u64,
+    pub error: u64,
+}
+
+impl Default for TtlSettings {
+    fn default() -> Self {
+        TtlSettings {
+            default: 4 * 60 * 60,
+            videos: 24 * 60 * 60,
+            watch: 6 * 60 * 60,
+            transcript: 24 * 60 * 60,
+            playlist: 12 * 60 * 60,
+            channel: 30 * 60,
+            feed: 15 * 60,
+            empty: 5 * 60,
+            error: 60,
+        }
+    }
+}
+
+impl TtlSettings {
+    /// Resolves the TTL for a cache key from its namespace prefix.
+    pub fn for_key(&self, key: &str) -> Duration {
+        let secs = match key.split('_').next().unwrap_or("") {
+            "videos" => self.videos,
+            "watch" => self.watch,
+            "transcript" => self.transcript,
+            "playlist" => self.playlist,
+            "channel" => self.channel,
+            "feed" => self.feed,
+            _ => self.default,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        CacheSettings {
+            backend: CacheBackendKind::Memory,
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            redis_prefix: "hyper_fetch:".to_string(),
+            max_entries: 10_000,
+            max_bytes: 128 * 1024 * 1024,
+            sweep_interval_secs: 5 * 60,
+            ttl: TtlSettings::default(),
+            stale_grace_secs: 60 * 60,
+            snapshot_path: None,
+            snapshot_interval_secs: 5 * 60,
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Result<Self, ConfigError> {
+        Config::builder()
+            .add_source(File::with_name("hyper_fetch").required(false))
+            .add_source(
+                Environment::with_prefix("HYPER_FETCH")
+                    .prefix_separator("_")
+                    .separator("__")
+                    .try_parsing(true)
+                    .list_separator(",")
+                    .with_list_parse_key("warmup.jobs")
+                    .with_list_parse_key("warmup.videos")
+                    .with_list_parse_key("channels.curated"),
+            )
+            .build()?
+            .try_deserialize()
+    }
+}